@@ -0,0 +1,158 @@
+use std::{fmt, ops::Range};
+
+use chumsky::prelude::*;
+
+pub type Spanned<T> = (T, Range<usize>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Str(_) => "Str",
+            Value::Int(_) => "Int",
+            Value::Bool(_) => "Bool",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Str(s) => write!(f, "{:?}", s),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+/// A `key = value` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub key: Spanned<String>,
+    pub value: Spanned<Value>,
+}
+
+/// Parser of a config file made of `key = value` lines.
+///
+/// Lines may be empty or end with a `#` comment. A malformed line is skipped after
+/// reporting an error, so that every broken line of a file is reported at once.
+pub fn parser() -> impl Parser<char, Vec<Entry>, Error = Simple<char>> {
+    let inline_ws = one_of(" \t\r").repeated().ignored();
+
+    let comment = just('#')
+        .then(filter(|c: &char| *c != '\n').repeated())
+        .ignored();
+
+    let string = just('"')
+        .ignore_then(filter(|c: &char| *c != '"' && *c != '\n').repeated())
+        .then_ignore(just('"'))
+        .collect::<String>()
+        .map(Value::Str)
+        .labelled("string");
+
+    let int = just('-')
+        .or_not()
+        .chain::<char, _, _>(text::digits(10))
+        .collect::<String>()
+        .try_map(|s, span| {
+            s.parse()
+                .map(Value::Int)
+                .map_err(|e| Simple::custom(span, format!("invalid integer {}: {}", s, e)))
+        })
+        .labelled("integer");
+
+    // Any other bare word is rejected here rather than with a vague keyword error,
+    // e.g. for `debug = yes`
+    let boolean = text::ident()
+        .try_map(|word: String, span| match word.as_str() {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            _ => Err(Simple::custom(
+                span,
+                format!("{} is not a value, strings must be quoted", word),
+            )),
+        })
+        .labelled("boolean");
+
+    let value = string
+        .or(int)
+        .or(boolean)
+        .map_with_span(|value, span| (value, span))
+        .labelled("value");
+
+    let key = text::ident()
+        .map_with_span(|key, span| (key, span))
+        .labelled("key");
+
+    let entry = key
+        .then_ignore(inline_ws.clone())
+        .then_ignore(just('=').labelled("'='"))
+        .then_ignore(inline_ws.clone())
+        .then(value)
+        .map(|(key, value)| Entry { key, value });
+
+    let line = inline_ws
+        .clone()
+        .ignore_then(entry.or_not())
+        .then_ignore(inline_ws)
+        .then_ignore(comment.or_not())
+        .then_ignore(just('\n').rewind().ignored().or(end()))
+        .recover_with(skip_until(['\n'], |_| None));
+
+    line.separated_by(just('\n'))
+        .then_ignore(end())
+        .map(|lines| lines.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let src = "# server\nname = \"app\"\n\nport=8080 # http\n  debug = false\n";
+        let entries = parser().parse(src).unwrap();
+
+        assert_eq!(
+            entries,
+            vec![
+                Entry {
+                    key: ("name".to_string(), 9..13),
+                    value: (Value::Str("app".to_string()), 16..21),
+                },
+                Entry {
+                    key: ("port".to_string(), 23..27),
+                    value: (Value::Int(8080), 28..32),
+                },
+                Entry {
+                    key: ("debug".to_string(), 42..47),
+                    value: (Value::Bool(false), 50..55),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recovery() {
+        let src = "name = app\nport = 80x\nworkers = -4\nmode \"a\"\n";
+        let (entries, errs) = parser().parse_recovery(src);
+
+        assert_eq!(
+            entries.unwrap(),
+            vec![Entry {
+                key: ("workers".to_string(), 22..29),
+                value: (Value::Int(-4), 32..34),
+            }]
+        );
+        assert_eq!(
+            errs.iter().map(|e| e.span().start).collect::<Vec<_>>(),
+            vec![7, 20, 40]
+        );
+    }
+}
@@ -0,0 +1,438 @@
+use std::{collections::HashMap, ops::Range, path::Path};
+
+use ariadne::{Color, Config, Label, Report, ReportKind};
+use chumsky::prelude::*;
+
+use crate::{
+    config::{parser, Entry, Value},
+    Span,
+};
+
+/// Type and constraints of a known key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Str,
+    Int { min: i64, max: i64 },
+    Bool,
+    OneOf(&'static [&'static str]),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    pub key: &'static str,
+    pub kind: Kind,
+    pub required: bool,
+}
+
+pub const SCHEMA: &[Field] = &[
+    Field {
+        key: "name",
+        kind: Kind::Str,
+        required: true,
+    },
+    Field {
+        key: "port",
+        kind: Kind::Int { min: 1, max: 65535 },
+        required: true,
+    },
+    Field {
+        key: "workers",
+        kind: Kind::Int { min: 1, max: 256 },
+        required: false,
+    },
+    Field {
+        key: "debug",
+        kind: Kind::Bool,
+        required: false,
+    },
+    Field {
+        key: "log_level",
+        kind: Kind::OneOf(&["error", "warn", "info", "debug", "trace"]),
+        required: false,
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A lint result, independent of how it is rendered.
+///
+/// The first label is the primary one, the others give context such as where a duplicated
+/// key was first defined. There are no labels when there is no code to point at, e.g. for
+/// a key missing from an empty file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: u32,
+    pub message: String,
+    pub labels: Vec<(Range<usize>, String)>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn report(&self, path: &Path, color: bool) -> Report<Span> {
+        let (kind, primary) = match self.severity {
+            Severity::Error => (ReportKind::Error, Color::Red),
+            Severity::Warning => (ReportKind::Warning, Color::Yellow),
+        };
+
+        let code = match self.severity {
+            Severity::Error => format!("E{:02}", self.code),
+            Severity::Warning => format!("W{:02}", self.code),
+        };
+
+        // Without a label, ariadne shows no source and so no file name
+        let message = if self.labels.is_empty() {
+            format!("{} in {}", self.message, path.display())
+        } else {
+            self.message.clone()
+        };
+        let offset = self.labels.first().map_or(0, |(span, _)| span.start);
+
+        let mut builder = Report::build(kind, path, offset)
+            .with_code(code)
+            .with_config(Config::default().with_color(color))
+            .with_message(message);
+
+        for (i, (span, message)) in self.labels.iter().enumerate() {
+            let label = Label::new(Span::new(path, span.clone()))
+                .with_message(message)
+                .with_order(i as i32);
+
+            // ariadne 0.1 draws label colors even without `Config::with_color`
+            builder = builder.with_label(if color {
+                label.with_color(if i == 0 { primary } else { Color::Blue })
+            } else {
+                label
+            });
+        }
+
+        if let Some(help) = &self.help {
+            builder = builder.with_help(help);
+        }
+
+        builder.finish()
+    }
+}
+
+fn syntax_error(e: &Simple<char>) -> Diagnostic {
+    let message = match e.reason() {
+        chumsky::error::SimpleReason::Custom(msg) => msg.clone(),
+        _ => format!(
+            "unexpected {}{}",
+            match e.found() {
+                Some(c) => format!("token {:?}", c),
+                None => "end of input".to_string(),
+            },
+            match e.label() {
+                Some(label) => format!(" while parsing {}", label),
+                None => String::new(),
+            }
+        ),
+    };
+
+    let mut expected = e
+        .expected()
+        .map(|expected| match expected {
+            Some(c) => format!("{:?}", c),
+            None => "end of input".to_string(),
+        })
+        .collect::<Vec<_>>();
+    expected.sort();
+
+    Diagnostic {
+        severity: Severity::Error,
+        code: 1,
+        message: "Syntax error".to_string(),
+        labels: vec![(e.span(), message)],
+        help: if expected.is_empty() {
+            None
+        } else {
+            Some(format!("expected {}", expected.join(", ")))
+        },
+    }
+}
+
+/// Levenshtein distance, used to suggest known keys for typos.
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = (prev + usize::from(ca != *cb)).min(row[j] + 1).min(cur + 1);
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+fn check_value(field: &Field, entry: &Entry) -> Option<Diagnostic> {
+    let (value, span) = &entry.value;
+
+    let (expected, message) = match (field.kind, value) {
+        (Kind::Str, Value::Str(_)) | (Kind::Bool, Value::Bool(_)) => return None,
+        (Kind::Int { min, max }, Value::Int(i)) => {
+            if (min..=max).contains(i) {
+                return None;
+            }
+            (
+                format!("an Int between {} and {}", min, max),
+                format!("{} is out of range", i),
+            )
+        }
+        (Kind::OneOf(choices), Value::Str(s)) => {
+            if choices.contains(&s.as_str()) {
+                return None;
+            }
+            (
+                format!("one of {}", choices.join(", ")),
+                format!("{:?} is not allowed", s),
+            )
+        }
+        (kind, value) => {
+            let expected = match kind {
+                Kind::Str | Kind::OneOf(_) => "Str",
+                Kind::Int { .. } => "Int",
+                Kind::Bool => "Bool",
+            };
+            (
+                format!("a value of type {}", expected),
+                format!("This is of type {}", value.type_name()),
+            )
+        }
+    };
+
+    Some(Diagnostic {
+        severity: Severity::Error,
+        code: 4,
+        message: format!("Invalid value for {}", field.key),
+        labels: vec![
+            (span.clone(), message),
+            (
+                entry.key.1.clone(),
+                format!("{} expects {}", field.key, expected),
+            ),
+        ],
+        help: None,
+    })
+}
+
+/// Checks `entries` against `schema`.
+pub fn check(entries: &[Entry], schema: &[Field]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut seen: HashMap<&str, &Entry> = HashMap::new();
+
+    for entry in entries {
+        let key = entry.key.0.as_str();
+
+        if let Some(first) = seen.get(key) {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                code: 2,
+                message: format!("Duplicate key {}", key),
+                labels: vec![
+                    (entry.key.1.clone(), "Defined again here".to_string()),
+                    (first.key.1.clone(), "First defined here".to_string()),
+                ],
+                help: Some("Remove one of the definitions".to_string()),
+            });
+            continue;
+        }
+        seen.insert(key, entry);
+
+        match schema.iter().find(|field| field.key == key) {
+            Some(field) => diagnostics.extend(check_value(field, entry)),
+            None => {
+                let suggestion = schema
+                    .iter()
+                    .map(|field| (distance(key, field.key), field.key))
+                    .filter(|(d, _)| *d <= 2)
+                    .min();
+
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    code: 3,
+                    message: format!("Unknown key {}", key),
+                    labels: vec![(entry.key.1.clone(), "This key is never read".to_string())],
+                    help: Some(match suggestion {
+                        Some((_, known)) => format!("Did you mean {}?", known),
+                        None => format!(
+                            "Known keys are {}",
+                            schema
+                                .iter()
+                                .map(|field| field.key)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    }),
+                });
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Reports the required keys of `schema` that `entries` lack, at `document`, the span of
+/// the whole file.
+///
+/// Keys in `broken` are skipped, as their lines were already reported as syntax errors.
+pub fn missing(
+    entries: &[Entry],
+    schema: &[Field],
+    document: Range<usize>,
+    broken: &[String],
+) -> Vec<Diagnostic> {
+    schema
+        .iter()
+        .filter(|field| field.required)
+        .filter(|field| !entries.iter().any(|entry| entry.key.0 == field.key))
+        .filter(|field| !broken.iter().any(|key| key == field.key))
+        .map(|field| Diagnostic {
+            severity: Severity::Error,
+            code: 5,
+            message: format!("Missing key {}", field.key),
+            // An empty file has no line to point at, and ariadne 0.1 panics on a label in it
+            labels: if document.is_empty() {
+                Vec::new()
+            } else {
+                vec![(document.clone(), format!("{} is required", field.key))]
+            },
+            help: Some(format!("Add a line such as `{} = ...`", field.key)),
+        })
+        .collect()
+}
+
+/// The leading word of the line of each error, which is the key of an entry the parser
+/// skipped.
+fn broken_keys(src: &str, errs: &[Simple<char>]) -> Vec<String> {
+    let chars = src.chars().collect::<Vec<_>>();
+
+    errs.iter()
+        .map(|e| {
+            let start = e.span().start.min(chars.len());
+            let line = chars[..start]
+                .iter()
+                .rposition(|c| *c == '\n')
+                .map_or(0, |i| i + 1);
+
+            chars[line..]
+                .iter()
+                .skip_while(|c| c.is_whitespace() && **c != '\n')
+                .take_while(|c| c.is_alphanumeric() || **c == '_')
+                .collect()
+        })
+        .collect()
+}
+
+/// Parses `src` and runs all lint rules on it.
+pub fn lint(src: &str) -> Vec<Diagnostic> {
+    let (entries, errs) = parser().parse_recovery(src);
+    let entries = entries.unwrap_or_default();
+    let document = 0..src.trim_end().chars().count();
+
+    let mut diagnostics = errs.iter().map(syntax_error).collect::<Vec<_>>();
+    diagnostics.extend(check(&entries, SCHEMA));
+    diagnostics.extend(missing(
+        &entries,
+        SCHEMA,
+        document,
+        &broken_keys(src, &errs),
+    ));
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(diagnostics: &[Diagnostic]) -> Vec<u32> {
+        diagnostics.iter().map(|d| d.code).collect()
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance("port", "port"), 0);
+        assert_eq!(distance("prot", "port"), 2);
+        assert_eq!(distance("worker", "workers"), 1);
+        assert_eq!(distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_ok() {
+        let src = "name = \"app\"\nport = 8080\nlog_level = \"info\"\n";
+        assert!(lint(src).is_empty());
+    }
+
+    #[test]
+    fn test_duplicate() {
+        let src = "name = \"app\"\nport = 80\nport = 8080\n";
+        let diagnostics = lint(src);
+
+        assert_eq!(codes(&diagnostics), vec![2]);
+        assert_eq!(
+            diagnostics[0].labels,
+            vec![
+                (23..27, "Defined again here".to_string()),
+                (13..17, "First defined here".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown() {
+        let src = "name = \"app\"\nport = 80\nworker = 4\ncolor = true\n";
+        let diagnostics = lint(src);
+
+        assert_eq!(codes(&diagnostics), vec![3, 3]);
+        assert_eq!(
+            diagnostics[0].help.as_deref(),
+            Some("Did you mean workers?")
+        );
+        assert!(diagnostics[1]
+            .help
+            .as_deref()
+            .unwrap()
+            .starts_with("Known keys are name, port"));
+    }
+
+    #[test]
+    fn test_invalid_value() {
+        let src = "name = 1\nport = 70000\nlog_level = \"loud\"\ndebug = \"yes\"\nworkers = 4\n";
+        let diagnostics = lint(src);
+
+        assert_eq!(codes(&diagnostics), vec![4, 4, 4, 4]);
+        assert_eq!(diagnostics[0].labels[0].1, "This is of type Int");
+        assert_eq!(diagnostics[1].labels[0].1, "70000 is out of range");
+        assert_eq!(diagnostics[2].labels[0].1, "\"loud\" is not allowed");
+        assert_eq!(
+            diagnostics[3].labels[1].1,
+            "debug expects a value of type Bool"
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        let diagnostics = lint("");
+
+        assert_eq!(codes(&diagnostics), vec![5, 5]);
+        assert!(diagnostics.iter().all(|d| d.labels.is_empty()));
+    }
+
+    #[test]
+    fn test_missing_and_syntax() {
+        let diagnostics = lint("name \"app\"\n");
+
+        // name is only reported as broken, port as missing from the whole file
+        assert_eq!(codes(&diagnostics), vec![1, 5]);
+        assert_eq!(diagnostics[0].labels[0].0, 5..6);
+        assert_eq!(diagnostics[1].message, "Missing key port");
+        assert_eq!(diagnostics[1].labels[0].0, 0..10);
+    }
+}
@@ -1,16 +1,189 @@
-use ariadne::{Color, Fmt, Label, Report, ReportKind, Source};
+mod config;
+mod lint;
+mod showcase;
+
+use std::{
+    io::Write,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use ariadne::{Cache, FileCache, Report};
+
+/// A char range in the file at `path`, whose source is read through a `FileCache`.
+#[derive(Debug, Clone)]
+struct Span {
+    path: PathBuf,
+    range: Range<usize>,
+}
+
+impl Span {
+    fn new(path: &Path, range: Range<usize>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            range,
+        }
+    }
+}
+
+impl ariadne::Span for Span {
+    type SourceId = Path;
+
+    fn source(&self) -> &Path {
+        &self.path
+    }
+
+    fn start(&self) -> usize {
+        self.range.start
+    }
+
+    fn end(&self) -> usize {
+        self.range.end
+    }
+}
+
+const USAGE: &str = "usage: ariadne-sample [--no-color] [showcase [SAMPLES_DIR] | lint <FILE>...]";
+
+fn write<C: Cache<Path>, W: Write>(
+    reports: &[Report<Span>],
+    cache: &mut C,
+    mut w: W,
+) -> std::io::Result<()> {
+    for report in reports {
+        report.write(&mut *cache, &mut w)?;
+    }
+    Ok(())
+}
+
+fn read(path: &Path) -> String {
+    std::fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("failed to read {}: {}", path.display(), err);
+        std::process::exit(2);
+    })
+}
+
+/// Renders the reports of every file in `paths` and returns whether an error was found.
+fn lint_files(paths: &[PathBuf], color: bool, w: impl Write) -> std::io::Result<bool> {
+    let mut reports = Vec::new();
+    let mut failed = false;
+
+    for path in paths {
+        for diagnostic in lint::lint(&read(path)) {
+            failed |= diagnostic.severity == lint::Severity::Error;
+            reports.push(diagnostic.report(path, color));
+        }
+    }
+
+    write(&reports, &mut FileCache::default(), w)?;
+    Ok(failed)
+}
 
 fn main() {
-    let src_id = "input.txt";
-    let src = "2022a/03/19";
-
-    Report::build(ReportKind::Error, src_id, 4)
-        .with_message("Unexpected char")
-        .with_label(
-            Label::new((src_id, 4..5))
-                .with_message(format!("unexpected char {}", "a".fg(Color::Red))),
-        )
-        .finish()
-        .print((src_id, Source::from(src)))
-        .unwrap();
+    let mut color = true;
+    let mut args = Vec::new();
+
+    for arg in std::env::args().skip(1) {
+        if arg == "--no-color" {
+            color = false;
+        } else if arg.starts_with("--") {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        } else {
+            args.push(arg);
+        }
+    }
+
+    match args.first().map(String::as_str) {
+        None | Some("showcase") => {
+            let dir = match args.get(1) {
+                Some(dir) => dir.into(),
+                None => Path::new(env!("CARGO_MANIFEST_DIR")).join("samples"),
+            };
+
+            let mut cache = FileCache::default();
+            let reports = showcase::reports(&dir, &mut cache, color).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                std::process::exit(2);
+            });
+
+            write(&reports, &mut cache, std::io::stderr()).unwrap();
+        }
+        Some("lint") if args.len() > 1 => {
+            let paths = args[1..].iter().map(PathBuf::from).collect::<Vec<_>>();
+
+            if lint_files(&paths, color, std::io::stderr()).unwrap() {
+                std::process::exit(1);
+            }
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod golden {
+    use std::path::PathBuf;
+
+    /// Compares `actual` with `golden/{name}.txt`.
+    ///
+    /// Run with `UPDATE_GOLDEN=1` to write the golden files instead, after an intended
+    /// change of the output or for a new test.
+    pub fn assert_golden(name: &str, actual: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("golden")
+            .join(format!("{}.txt", name));
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, actual).unwrap();
+            return;
+        }
+
+        let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+            panic!(
+                "golden file {} is missing ({}), run with UPDATE_GOLDEN=1 to create it",
+                name, e
+            )
+        });
+        assert_eq!(expected, actual, "golden file {} does not match", name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Relative to the package root, where cargo runs tests, so that headers in the golden
+    /// file do not depend on the checkout.
+    fn sample(name: &str) -> PathBuf {
+        Path::new("samples").join(name)
+    }
+
+    #[test]
+    fn test_lint_files() {
+        let mut buf = Vec::new();
+        assert!(!lint_files(&[sample("ok.conf")], false, &mut buf).unwrap());
+        assert!(buf.is_empty());
+
+        let mut buf = Vec::new();
+        assert!(lint_files(&[sample("ok.conf"), sample("app.conf")], false, &mut buf).unwrap());
+
+        let out = String::from_utf8(buf).unwrap();
+        assert!(out.contains("app.conf:7:1"));
+        assert!(out.contains("First defined here"));
+        assert!(out.contains("Did you mean workers?"));
+        crate::golden::assert_golden("lint", &out);
+    }
+
+    #[test]
+    fn test_lint_empty() {
+        let mut buf = Vec::new();
+        assert!(lint_files(&[sample("empty.conf")], false, &mut buf).unwrap());
+
+        let out = String::from_utf8(buf).unwrap();
+        assert!(out.contains("Missing key name in samples/empty.conf"));
+        assert!(out.contains("Missing key port in samples/empty.conf"));
+    }
 }
@@ -0,0 +1,182 @@
+use std::{ops::Range, path::Path};
+
+use ariadne::{
+    Cache, Color, ColorGenerator, Config, FileCache, Fmt, Label, Report, ReportKind, Source,
+};
+
+use crate::Span;
+
+pub const FILES: [&str; 2] = ["main.calc", "lib.calc"];
+
+/// Char range of the first occurrence of `needle` in `source`, which must not span lines.
+///
+/// Ariadne counts offsets in chars, not bytes.
+fn span_of(source: &Source, needle: &str) -> Option<Range<usize>> {
+    source.lines().find_map(|line| {
+        let text = line.chars().collect::<String>();
+        let start = line.offset() + text[..text.find(needle)?].chars().count();
+        Some(start..start + needle.chars().count())
+    })
+}
+
+/// Span of `needle` in the file at `path`, as read through `cache`.
+fn find(cache: &mut FileCache, path: &Path, needle: &str) -> Result<Span, String> {
+    let source = cache
+        .fetch(path)
+        .map_err(|e| format!("failed to read {}: {:?}", path.display(), e))?;
+    let range = span_of(source, needle)
+        .ok_or_else(|| format!("{} does not contain {}", path.display(), needle))?;
+    Ok(Span::new(path, range))
+}
+
+fn paint(text: &str, color: Color, enabled: bool) -> String {
+    if enabled {
+        text.fg(color).to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// `label` colored with `color` if `enabled`, as ariadne 0.1 draws label colors even
+/// without `Config::with_color`.
+fn colored(label: Label<Span>, color: Color, enabled: bool) -> Label<Span> {
+    if enabled {
+        label.with_color(color)
+    } else {
+        label
+    }
+}
+
+/// Builds reports against the sample files in `dir`, which `cache` reads.
+///
+/// Fails if a sample file cannot be read or no longer contains the code reported on.
+pub fn reports(
+    dir: &Path,
+    cache: &mut FileCache,
+    color: bool,
+) -> Result<Vec<Report<Span>>, String> {
+    let main = dir.join(FILES[0]);
+    let lib = dir.join(FILES[1]);
+    let literal = find(cache, &main, "\"2\"")?;
+    let param = find(cache, &lib, "b: Int")?;
+    let call = find(cache, &main, "add(one, \"2\")")?;
+    let sub = find(cache, &lib, "sub")?;
+    let config = Config::default().with_color(color);
+
+    let mut colors = ColorGenerator::new();
+    let a = colors.next();
+    let b = colors.next();
+    let c = colors.next();
+
+    let incompatible = Report::build(ReportKind::Error, &main, literal.range.start)
+        .with_code(1)
+        .with_config(config)
+        .with_message("Incompatible types")
+        .with_label(colored(
+            Label::new(literal)
+                .with_message(format!("This is of type {}", paint("Str", a, color)))
+                .with_order(0),
+            a,
+            color,
+        ))
+        .with_label(colored(
+            Label::new(param)
+                .with_message(format!(
+                    "Parameter {} is declared as {} here",
+                    paint("b", b, color),
+                    paint("Int", b, color)
+                ))
+                .with_order(1),
+            b,
+            color,
+        ))
+        .with_label(colored(
+            Label::new(call)
+                .with_message(format!("In this call to {}", paint("add", c, color)))
+                .with_order(2),
+            c,
+            color,
+        ))
+        .with_note(format!(
+            "{} values are never converted to {} implicitly",
+            paint("Str", a, color),
+            paint("Int", b, color)
+        ))
+        .with_help("Pass a number literal such as 2 instead")
+        .finish();
+
+    let unused = Report::build(ReportKind::Warning, &lib, sub.range.start)
+        .with_code(2)
+        .with_config(config)
+        .with_message("Unused function")
+        .with_label(colored(
+            Label::new(sub).with_message(format!(
+                "{} is never called",
+                paint("sub", Color::Yellow, color)
+            )),
+            Color::Yellow,
+            color,
+        ))
+        .with_help("Remove it, or call it from main.calc")
+        .finish();
+
+    Ok(vec![incompatible, unused])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{golden::assert_golden, write};
+
+    fn render(color: bool) -> String {
+        // Relative to the package root, where cargo runs tests, so that headers in the
+        // golden file do not depend on the checkout.
+        let mut cache = FileCache::default();
+        let reports = reports(Path::new("samples"), &mut cache, color).unwrap();
+        let mut buf = Vec::new();
+        write(&reports, &mut cache, &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_span_of() {
+        assert_eq!(span_of(&Source::from("abc"), "bc"), Some(1..3));
+        assert_eq!(span_of(&Source::from("あいう"), "う"), Some(2..3));
+        assert_eq!(span_of(&Source::from("a\nいう"), "う"), Some(3..4));
+        assert_eq!(span_of(&Source::from("abc"), "d"), None);
+    }
+
+    #[test]
+    fn test_missing() {
+        let mut cache = FileCache::default();
+        let err = reports(Path::new("no-such-dir"), &mut cache, false)
+            .err()
+            .unwrap();
+        assert!(err.starts_with("failed to read no-such-dir/main.calc"));
+
+        // lib.calc does not contain the call reported on
+        let err = find(&mut cache, Path::new("samples/lib.calc"), "add(one")
+            .err()
+            .unwrap();
+        assert_eq!(err, "samples/lib.calc does not contain add(one");
+    }
+
+    #[test]
+    fn test_plain() {
+        let out = render(false);
+        assert!(!out.contains('\x1b'));
+        assert!(out.contains("main.calc:4:"));
+        assert!(out.contains("lib.calc:1:"));
+        assert!(out.contains("This is of type Str"));
+        assert!(out.contains("Parameter b is declared as Int here"));
+        assert!(out.contains("In this call to add"));
+        assert!(out.contains("Pass a number literal such as 2 instead"));
+        assert!(out.contains("sub is never called"));
+        assert_golden("plain", &out);
+    }
+
+    #[test]
+    fn test_color() {
+        assert!(render(true).contains('\x1b'));
+    }
+}
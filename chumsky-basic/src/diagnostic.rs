@@ -0,0 +1,296 @@
+use std::{collections::HashMap, fmt::Display, hash::Hash, io::Write, ops::Range};
+
+use ariadne::{Cache, Color, Config, Fmt, Label, Report, ReportKind};
+use chumsky::error::{Simple, SimpleReason};
+
+pub type Span = (String, Range<usize>);
+
+/// Turns chumsky's `Simple` errors into ariadne reports.
+///
+/// Every report is tied to a source id, so errors coming from several files can be
+/// rendered against a single ariadne cache.
+pub struct Renderer {
+    color: bool,
+    helps: HashMap<&'static str, String>,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Self {
+            color: true,
+            helps: HashMap::new(),
+        }
+    }
+
+    /// Enables or disables ANSI colors in the rendered output.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Attaches a help line to every error raised while parsing `label`.
+    pub fn with_help(mut self, label: &'static str, help: impl ToString) -> Self {
+        self.helps.insert(label, help.to_string());
+        self
+    }
+
+    fn fg<T: Display>(&self, item: T, color: Color) -> String {
+        if self.color {
+            item.fg(color).to_string()
+        } else {
+            item.to_string()
+        }
+    }
+
+    /// `label` colored with `color`, which ariadne 0.1 draws even without `Config::with_color`.
+    fn colored(&self, label: Label<Span>, color: Color) -> Label<Span> {
+        if self.color {
+            label.with_color(color)
+        } else {
+            label
+        }
+    }
+
+    fn token<T: Display>(&self, token: Option<&T>) -> String {
+        match token {
+            Some(token) => format!("token {}", self.fg(format!("'{}'", token), Color::Red)),
+            None => "end of input".to_string(),
+        }
+    }
+
+    /// The headline of the report, e.g. "unexpected token while parsing mm".
+    pub fn message<T: Display + Hash + Eq>(&self, error: &Simple<T>) -> String {
+        match error.reason() {
+            SimpleReason::Custom(msg) => msg.clone(),
+            _ => format!(
+                "unexpected {}{}",
+                if error.found().is_some() {
+                    "token"
+                } else {
+                    "end of input"
+                },
+                match error.label() {
+                    Some(label) => format!(" while parsing {}", self.fg(label, Color::Green)),
+                    None => String::new(),
+                }
+            ),
+        }
+    }
+
+    /// The message attached to the primary label, which points at `error.span()`.
+    pub fn label_message<T: Display + Hash + Eq>(&self, error: &Simple<T>) -> String {
+        match error.reason() {
+            SimpleReason::Custom(msg) => msg.clone(),
+            _ => format!("unexpected {}", self.token(error.found())),
+        }
+    }
+
+    /// The help line registered by `with_help` for the label of `error`, if any.
+    pub fn help<T: Display + Hash + Eq>(&self, error: &Simple<T>) -> Option<&str> {
+        error
+            .label()
+            .and_then(|label| self.helps.get(label))
+            .map(String::as_str)
+    }
+
+    pub fn report<T: Display + Hash + Eq>(&self, src_id: &str, error: &Simple<T>) -> Report<Span> {
+        let span = error.span();
+
+        let mut builder = Report::build(ReportKind::Error, src_id, span.start)
+            .with_config(Config::default().with_color(self.color))
+            .with_message(self.message(error))
+            .with_label(
+                self.colored(
+                    Label::new((src_id.to_string(), span))
+                        .with_message(self.label_message(error))
+                        .with_order(0),
+                    Color::Red,
+                ),
+            );
+
+        if let SimpleReason::Unclosed { span, delimiter } = error.reason() {
+            builder = builder.with_label(
+                self.colored(
+                    Label::new((src_id.to_string(), span.clone()))
+                        .with_message(format!(
+                            "delimiter {} was never closed",
+                            self.fg(format!("'{}'", delimiter), Color::Yellow)
+                        ))
+                        .with_order(1),
+                    Color::Yellow,
+                ),
+            );
+        }
+
+        let expected = expected(error);
+        if !expected.is_empty() {
+            builder = builder.with_note(format!("expected {}", expected.join(", ")));
+        }
+
+        if let Some(help) = self.help(error) {
+            builder = builder.with_help(help);
+        }
+
+        builder.finish()
+    }
+
+    /// Writes reports of all `errors` found in `src_id` to `w`.
+    pub fn write<T: Display + Hash + Eq, C: Cache<String>, W: Write>(
+        &self,
+        cache: &mut C,
+        src_id: &str,
+        errors: &[Simple<T>],
+        mut w: W,
+    ) -> std::io::Result<()> {
+        for error in errors {
+            self.report(src_id, error).write(&mut *cache, &mut w)?;
+        }
+        Ok(())
+    }
+
+    /// Same as `write` but into a `String`.
+    #[cfg(test)]
+    pub fn render<T: Display + Hash + Eq, C: Cache<String>>(
+        &self,
+        cache: &mut C,
+        src_id: &str,
+        errors: &[Simple<T>],
+    ) -> String {
+        let mut buf = Vec::new();
+        self.write(cache, src_id, errors, &mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+}
+
+/// Tokens that would have been accepted at the error position, sorted to get a
+/// stable output since chumsky keeps them in a `HashSet`.
+pub fn expected<T: Display + Hash + Eq>(error: &Simple<T>) -> Vec<String> {
+    let mut expected = error
+        .expected()
+        .map(|expected| match expected {
+            Some(expected) => format!("'{}'", expected),
+            None => "end of input".to_string(),
+        })
+        .collect::<Vec<_>>();
+    expected.sort();
+    expected
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use ariadne::sources;
+    use chumsky::prelude::*;
+
+    use super::*;
+    use crate::yyyy_mm_dd;
+
+    /// Compares `actual` with `src/snapshots/{name}.txt`.
+    ///
+    /// Run with `UPDATE_SNAPSHOTS=1` to write the snapshots instead, after an intended
+    /// change of the output or for a new test.
+    fn assert_snapshot(name: &str, actual: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/snapshots")
+            .join(format!("{}.txt", name));
+
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, actual).unwrap();
+            return;
+        }
+
+        let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+            panic!(
+                "snapshot {} is missing ({}), run with UPDATE_SNAPSHOTS=1 to create it",
+                name, e
+            )
+        });
+        assert_eq!(expected, actual, "snapshot {} does not match", name);
+    }
+
+    fn render(src_id: &str, src: &str) -> String {
+        let (_, errs) = yyyy_mm_dd().parse_recovery(src);
+        let renderer = Renderer::new()
+            .with_color(false)
+            .with_help("mm", "months are written with two digits, e.g. 03");
+
+        renderer.render(&mut sources(vec![(src_id.to_string(), src)]), src_id, &errs)
+    }
+
+    #[test]
+    fn test_unexpected() {
+        let out = render("unexpected.txt", "2022@10@10");
+        assert!(out.contains("Error: unexpected token"));
+        assert!(out.contains("unexpected token '@'"));
+        assert!(out.contains("expected '/'"));
+        assert!(!out.contains('\x1b'));
+        assert_snapshot("unexpected", &out);
+    }
+
+    #[test]
+    fn test_custom() {
+        let out = render("custom.txt", "20221/03/19");
+        assert!(out.contains("length of a number must be 4, but got 20221"));
+        assert_snapshot("custom", &out);
+    }
+
+    #[test]
+    fn test_end_of_input_with_help() {
+        let out = render("eoi.txt", "2022/");
+        assert!(out.contains("unexpected end of input while parsing mm"));
+        assert!(out.contains("months are written with two digits, e.g. 03"));
+        assert_snapshot("end_of_input", &out);
+    }
+
+    #[test]
+    fn test_unclosed() {
+        let src = "(2022/03/19";
+        let error = Simple::unclosed_delimiter(0..1, '(', 11..11, ')', None);
+
+        let out = Renderer::new().with_color(false).render(
+            &mut sources(vec![("unclosed.txt".to_string(), src)]),
+            "unclosed.txt",
+            &[error],
+        );
+        assert!(out.contains("delimiter '(' was never closed"));
+        assert!(out.contains("expected ')'"));
+        assert_snapshot("unclosed", &out);
+    }
+
+    #[test]
+    fn test_multiple_sources() {
+        let mut cache = sources(vec![
+            ("a.txt".to_string(), "2022/03/1x"),
+            ("b.txt".to_string(), "2022-03-19"),
+        ]);
+        let renderer = Renderer::new().with_color(false);
+
+        let mut out = String::new();
+        for (src_id, src) in [("a.txt", "2022/03/1x"), ("b.txt", "2022-03-19")] {
+            let (_, errs) = yyyy_mm_dd().parse_recovery(src);
+            out += &renderer.render(&mut cache, src_id, &errs);
+        }
+        assert!(out.contains("a.txt:1:"));
+        assert!(out.contains("b.txt:1:"));
+        assert_snapshot("multiple_sources", &out);
+    }
+
+    #[test]
+    fn test_color() {
+        let (_, errs) = yyyy_mm_dd().parse_recovery("2022@10@10");
+        let out = Renderer::new().render(
+            &mut sources(vec![("color.txt".to_string(), "2022@10@10")]),
+            "color.txt",
+            &errs,
+        );
+        assert!(out.contains('\x1b'));
+    }
+}
@@ -1,5 +1,12 @@
-use ariadne::{Color, Fmt, Label, Report, ReportKind, Source};
-use chumsky::prelude::*;
+mod diagnostic;
+mod structured;
+mod table;
+
+use std::str::FromStr;
+
+use ariadne::sources;
+use chumsky::{prelude::*, Stream};
+use diagnostic::Renderer;
 
 fn yyyy_mm_dd() -> impl Parser<char, (u32, u32, u32), Error = Simple<char>> {
     let number = |len| {
@@ -29,6 +36,8 @@ fn yyyy_mm_dd() -> impl Parser<char, (u32, u32, u32), Error = Simple<char>> {
         .map(|((y, m), d)| (y, m, d))
 }
 
+// The literals are written as in the dates
+#[allow(clippy::zero_prefixed_literal)]
 #[test]
 fn test_yyyy_mm_dd() {
     assert_eq!(yyyy_mm_dd().parse("2020/03/19").unwrap(), (2020, 03, 19));
@@ -40,56 +49,295 @@ fn test_yyyy_mm_dd() {
     );
 }
 
+/// How errors are written to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// Colored ariadne reports
+    Human,
+    /// One JSON object per error and line
+    Json,
+    /// A single SARIF 2.1.0 log
+    Sarif,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Format::Human),
+            "json" => Ok(Format::Json),
+            "sarif" => Ok(Format::Sarif),
+            _ => Err(format!(
+                "unknown format {}, expected one of human, json, sarif",
+                s
+            )),
+        }
+    }
+}
+
+/// Which column of the table holds the dates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Column {
+    /// 0-based index
+    Index(usize),
+    /// Name in the header row
+    Name(String),
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.parse() {
+            Ok(index) => Column::Index(index),
+            Err(_) => Column::Name(s.to_string()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Args {
+    format: Format,
+    column: Column,
+    /// Inferred from the file extension when `None`
+    delimiter: Option<char>,
+    header: bool,
+    files: Vec<String>,
+}
+
+const USAGE: &str = "usage: chumsky-basic [--format=human|json|sarif] [--column=<index|name>] \
+[--delimiter=<char>] [--no-header] <FILE>...";
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        format: Format::Human,
+        column: Column::Index(0),
+        delimiter: None,
+        header: true,
+        files: Vec::new(),
+    };
+
+    for arg in args {
+        if let Some(format) = arg.strip_prefix("--format=") {
+            parsed.format = format.parse()?;
+        } else if let Some(column) = arg.strip_prefix("--column=") {
+            parsed.column = column.parse()?;
+        } else if let Some(delimiter) = arg.strip_prefix("--delimiter=") {
+            parsed.delimiter = Some(match delimiter {
+                "tab" | "\\t" => '\t',
+                _ => {
+                    let mut chars = delimiter.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => c,
+                        _ => return Err(format!("invalid delimiter {}", delimiter)),
+                    }
+                }
+            });
+        } else if arg == "--no-header" {
+            parsed.header = false;
+        } else if arg.starts_with("--") {
+            return Err(format!("unknown option {}", arg));
+        } else {
+            parsed.files.push(arg);
+        }
+    }
+
+    if parsed.files.is_empty() {
+        return Err("no input files".to_string());
+    }
+
+    Ok(parsed)
+}
+
+/// `\t` for `.tsv` files and `,` otherwise.
+fn delimiter_of(path: &str) -> char {
+    if path.to_lowercase().ends_with(".tsv") {
+        '\t'
+    } else {
+        ','
+    }
+}
+
+/// Validates the dates in `column` of a CSV/TSV `src`.
+///
+/// Returns the number of checked rows and the errors, whose spans point into `src`.
+fn validate(
+    src: &str,
+    delimiter: char,
+    column: &Column,
+    header: bool,
+) -> Result<(usize, Vec<Simple<char>>), String> {
+    let mut rows = table::parse(src, delimiter).into_iter();
+
+    let index = match (column, header) {
+        (Column::Index(index), true) => {
+            rows.next();
+            *index
+        }
+        (Column::Index(index), false) => *index,
+        (Column::Name(name), true) => rows
+            .next()
+            .and_then(|header| header.fields.iter().position(|f| &f.text == name))
+            .ok_or_else(|| format!("column {} is not found in the header", name))?,
+        (Column::Name(name), false) => {
+            return Err(format!(
+                "column {} is specified by name, but there is no header",
+                name
+            ))
+        }
+    };
+
+    let mut count = 0;
+    let mut errors = Vec::new();
+    for row in rows {
+        count += 1;
+        match row.fields.get(index) {
+            Some(field) => {
+                let (_, errs) = yyyy_mm_dd()
+                    .then_ignore(end())
+                    .parse_recovery(Stream::from_iter(
+                        field.end..field.end,
+                        field.spanned_chars(),
+                    ));
+                errors.extend(errs);
+            }
+            None => errors.push(Simple::custom(
+                row.end..row.end,
+                format!(
+                    "row at line {} has {} columns, but column {} is required",
+                    row.line,
+                    row.fields.len(),
+                    index
+                ),
+            )),
+        }
+    }
+
+    Ok((count, errors))
+}
+
+#[test]
+fn test_validate() {
+    let src = "id,date\n1,2022/03/19\n2,\"2021/june/10\"\n3\n4,2022/03/19x\n";
+
+    let (count, errs) = validate(src, ',', &Column::Name("date".to_string()), true).unwrap();
+    assert_eq!(count, 4);
+    assert_eq!(errs.len(), 3);
+    // "j" of "june"
+    assert_eq!(errs[0].span(), 29..30);
+    assert_eq!(errs[1].span(), 39..39);
+    assert_eq!(errs[2].span(), 52..53);
+
+    let (_, errs) = validate(src, ',', &Column::Index(1), true).unwrap();
+    assert_eq!(errs.len(), 3);
+
+    assert!(validate(src, ',', &Column::Name("day".to_string()), true).is_err());
+}
+
+#[test]
+fn test_parse_args() {
+    let args = parse_args(
+        [
+            "--format=json",
+            "--column=date",
+            "--delimiter=tab",
+            "a.tsv",
+            "b.tsv",
+        ]
+        .into_iter()
+        .map(String::from),
+    )
+    .unwrap();
+    assert_eq!(args.format, Format::Json);
+    assert_eq!(args.column, Column::Name("date".to_string()));
+    assert_eq!(args.delimiter, Some('\t'));
+    assert!(args.header);
+    assert_eq!(args.files, vec!["a.tsv", "b.tsv"]);
+
+    assert!(parse_args(std::iter::empty()).is_err());
+    assert!(parse_args(["--delimiter=ab", "a.csv"].into_iter().map(String::from)).is_err());
+}
+
 fn main() {
-    for src in ["20221/03/19", "2021/june/10", "2022@10@10", "2022/"] {
-        let (_, errs) = yyyy_mm_dd().parse_recovery(src);
-
-        for e in errs {
-            let message = match e.reason() {
-                chumsky::error::SimpleReason::Unexpected
-                | chumsky::error::SimpleReason::Unclosed { .. } => {
-                    format!(
-                        "{}{}, expected {}",
-                        if e.found().is_some() {
-                            "unexpected token"
-                        } else {
-                            "unexpected end of input"
-                        },
-                        if let Some(label) = e.label() {
-                            format!(" while parsing {}", label.fg(Color::Green))
-                        } else {
-                            " something else".to_string()
-                        },
-                        if e.expected().count() == 0 {
-                            "somemething else".to_string()
-                        } else {
-                            e.expected()
-                                .map(|expected| match expected {
-                                    Some(expected) => expected.to_string(),
-                                    None => "end of input".to_string(),
-                                })
-                                .collect::<Vec<_>>()
-                                .join(", ")
-                        }
-                    )
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{}\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let renderer = Renderer::new()
+        .with_color(args.format == Format::Human)
+        .with_help("yyyy", "years are written with four digits, e.g. 2022")
+        .with_help("mm", "months are written with two digits, e.g. 03")
+        .with_help("dd", "days are written with two digits, e.g. 19");
+
+    let mut inputs = Vec::new();
+    for path in &args.files {
+        match std::fs::read_to_string(path) {
+            Ok(src) => inputs.push((path.clone(), src)),
+            Err(err) => {
+                eprintln!("failed to read {}: {}", path, err);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let mut rows = 0;
+    let mut errors = Vec::new();
+    for (path, src) in &inputs {
+        let delimiter = args.delimiter.unwrap_or_else(|| delimiter_of(path));
+        match validate(src, delimiter, &args.column, args.header) {
+            Ok((count, errs)) => {
+                rows += count;
+                errors.push(errs);
+            }
+            Err(err) => {
+                eprintln!("{}: {}", path, err);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    match args.format {
+        Format::Human => {
+            let mut cache = sources(inputs.clone());
+            for ((path, _), errs) in inputs.iter().zip(&errors) {
+                renderer
+                    .write(&mut cache, path, errs, std::io::stdout())
+                    .unwrap();
+            }
+        }
+        Format::Json => {
+            for ((path, src), errs) in inputs.iter().zip(&errors) {
+                for e in errs {
+                    println!("{}", structured::json_line(&renderer, path, src, e));
                 }
-                chumsky::error::SimpleReason::Custom(msg) => msg.clone(),
-            };
-
-            Report::build(ReportKind::Error, (), e.span().start)
-                .with_message(message)
-                .with_label(Label::new(e.span()).with_message(match e.reason() {
-                    chumsky::error::SimpleReason::Custom(msg) => msg.clone(),
-                    _ => format!(
-                            "Unexpected {}",
-                            e.found()
-                                .map(|c| format!("token {}", c.fg(Color::Red)))
-                                .unwrap_or_else(|| "end of input".to_string())
-                        ),
-                }))
-                .finish()
-                .print(Source::from(src))
-                .unwrap();
+            }
+        }
+        Format::Sarif => {
+            let log = structured::sarif(
+                &renderer,
+                inputs
+                    .iter()
+                    .zip(&errors)
+                    .map(|((path, src), errs)| (path.as_str(), src.as_str(), errs.as_slice())),
+            );
+            println!("{}", serde_json::to_string_pretty(&log).unwrap());
         }
     }
+
+    let error_count = errors.iter().map(Vec::len).sum::<usize>();
+    eprintln!(
+        "checked {} rows in {} files: {} errors",
+        rows,
+        inputs.len(),
+        error_count
+    );
+
+    if error_count > 0 {
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,234 @@
+use std::{fmt::Display, hash::Hash, ops::Range};
+
+use chumsky::error::{Simple, SimpleReason};
+use serde_json::{json, Value};
+
+use crate::diagnostic::{expected, Renderer};
+
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const RULE_ID: &str = "parse-error";
+
+/// 1-based line and column of the `offset`-th char of `src`.
+///
+/// Spans of `Simple<char>` count chars, not bytes, so columns are in Unicode code points.
+pub fn line_col(src: &str, offset: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut col = 1;
+    for c in src.chars().take(offset) {
+        if c == '\n' {
+            line += 1;
+            col = 1;
+        } else {
+            col += 1;
+        }
+    }
+    (line, col)
+}
+
+fn position(src: &str, offset: usize) -> Value {
+    let (line, column) = line_col(src, offset);
+    json!({ "offset": offset, "line": line, "column": column })
+}
+
+fn reason<T: Hash + Eq>(error: &Simple<T>) -> &'static str {
+    match error.reason() {
+        SimpleReason::Unexpected => "unexpected",
+        SimpleReason::Unclosed { .. } => "unclosed",
+        SimpleReason::Custom(_) => "custom",
+    }
+}
+
+/// One error as a JSON object, suitable for emitting as a single line of JSON lines.
+pub fn json_line<T: Display + Hash + Eq>(
+    renderer: &Renderer,
+    src_id: &str,
+    src: &str,
+    error: &Simple<T>,
+) -> Value {
+    let span = error.span();
+
+    let mut value = json!({
+        "file": src_id,
+        "reason": reason(error),
+        "start": position(src, span.start),
+        "end": position(src, span.end),
+        "expected": expected(error),
+        "found": error.found().map(|found| found.to_string()),
+        "label": error.label(),
+        "message": renderer.message(error),
+        "label_message": renderer.label_message(error),
+    });
+
+    if let SimpleReason::Unclosed { span, delimiter } = error.reason() {
+        value["unclosed"] = json!({
+            "delimiter": delimiter.to_string(),
+            "start": position(src, span.start),
+            "end": position(src, span.end),
+        });
+    }
+
+    value
+}
+
+fn region(src: &str, span: &Range<usize>) -> Value {
+    let (start_line, start_column) = line_col(src, span.start);
+    let (end_line, end_column) = line_col(src, span.end);
+    json!({
+        "startLine": start_line,
+        "startColumn": start_column,
+        "endLine": end_line,
+        "endColumn": end_column,
+        "charOffset": span.start,
+        "charLength": span.end - span.start,
+    })
+}
+
+fn location(src_id: &str, src: &str, span: &Range<usize>) -> Value {
+    json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": src_id },
+            "region": region(src, span),
+        }
+    })
+}
+
+/// A SARIF 2.1.0 log with a single run containing the errors of all `files`.
+///
+/// `files` yields `(src_id, src, errors)`; `src_id` is used as the artifact URI.
+pub fn sarif<'a, T, I>(renderer: &Renderer, files: I) -> Value
+where
+    T: Display + Hash + Eq + 'a,
+    I: IntoIterator<Item = (&'a str, &'a str, &'a [Simple<T>])>,
+{
+    let mut results = Vec::new();
+
+    for (src_id, src, errors) in files {
+        for error in errors {
+            let mut message = renderer.message(error);
+            let expected = expected(error);
+            if !expected.is_empty() {
+                message += &format!(", expected {}", expected.join(", "));
+            }
+
+            let mut result = json!({
+                "ruleId": RULE_ID,
+                "level": "error",
+                "message": { "text": message },
+                "locations": [location(src_id, src, &error.span())],
+            });
+
+            if let SimpleReason::Unclosed { span, delimiter } = error.reason() {
+                result["relatedLocations"] = json!([{
+                    "id": 1,
+                    "message": { "text": format!("delimiter '{}' was never closed", delimiter) },
+                    "physicalLocation": location(src_id, src, span)["physicalLocation"],
+                }]);
+            }
+
+            results.push(result);
+        }
+    }
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": [{
+                        "id": RULE_ID,
+                        "shortDescription": { "text": "Input could not be parsed" },
+                    }],
+                }
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chumsky::prelude::*;
+
+    use super::*;
+    use crate::yyyy_mm_dd;
+
+    #[test]
+    fn test_line_col() {
+        let src = "ab\nあc\n";
+        assert_eq!(line_col(src, 0), (1, 1));
+        assert_eq!(line_col(src, 2), (1, 3));
+        assert_eq!(line_col(src, 3), (2, 1));
+        assert_eq!(line_col(src, 4), (2, 2));
+        assert_eq!(line_col(src, 6), (3, 1));
+    }
+
+    #[test]
+    fn test_json_line() {
+        let renderer = Renderer::new().with_color(false);
+        let src = "2022@10@10";
+        let (_, errs) = yyyy_mm_dd().parse_recovery(src);
+
+        let value = json_line(&renderer, "input", src, &errs[0]);
+        assert_eq!(value["file"], "input");
+        assert_eq!(value["reason"], "unexpected");
+        assert_eq!(
+            value["start"],
+            json!({ "offset": 4, "line": 1, "column": 5 })
+        );
+        assert_eq!(value["end"], json!({ "offset": 5, "line": 1, "column": 6 }));
+        assert_eq!(value["expected"], json!(["'/'"]));
+        assert_eq!(value["found"], "@");
+        assert_eq!(
+            value["message"],
+            "unexpected token while parsing slash between yyyy and mm"
+        );
+        assert_eq!(value["label_message"], "unexpected token '@'");
+        // Must fit on a single line
+        assert!(!value.to_string().contains('\n'));
+    }
+
+    #[test]
+    fn test_json_line_unclosed() {
+        let renderer = Renderer::new().with_color(false);
+        let src = "(2022/03/19";
+        let error = Simple::unclosed_delimiter(0..1, '(', 11..11, ')', None);
+
+        let value = json_line(&renderer, "input", src, &error);
+        assert_eq!(value["reason"], "unclosed");
+        assert_eq!(value["found"], Value::Null);
+        assert_eq!(value["unclosed"]["delimiter"], "(");
+        assert_eq!(value["unclosed"]["start"]["column"], 1);
+    }
+
+    #[test]
+    fn test_sarif() {
+        let renderer = Renderer::new().with_color(false);
+        let inputs = [("a.txt", "2022/03/19"), ("b.txt", "2022/03@19")];
+        let errs = inputs
+            .iter()
+            .map(|(_, src)| yyyy_mm_dd().parse_recovery(*src).1)
+            .collect::<Vec<_>>();
+
+        let log = sarif(
+            &renderer,
+            inputs
+                .iter()
+                .zip(&errs)
+                .map(|((src_id, src), errs)| (*src_id, *src, errs.as_slice())),
+        );
+
+        assert_eq!(log["version"], "2.1.0");
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        assert!(!results.is_empty());
+        for result in results {
+            let location = &result["locations"][0]["physicalLocation"];
+            assert_eq!(location["artifactLocation"]["uri"], "b.txt");
+            assert_eq!(location["region"]["startLine"], 1);
+            assert_eq!(location["region"]["startColumn"], 8);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use std::ops::Range;
+
+/// A field of a CSV/TSV row.
+///
+/// Quotes and escaped quotes are removed from `text`, so `offsets` keeps the char offset
+/// in the original file of each char of `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub text: String,
+    pub offsets: Vec<usize>,
+    /// Char offset in the file right after the last char of the field
+    pub end: usize,
+}
+
+impl Field {
+    /// `(char, span)` pairs with spans pointing into the original file, ready to be fed
+    /// into `chumsky::Stream::from_iter`.
+    pub fn spanned_chars(&self) -> impl Iterator<Item = (char, Range<usize>)> + '_ {
+        self.text
+            .chars()
+            .zip(&self.offsets)
+            .map(|(c, &offset)| (c, offset..offset + 1))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    /// 1-based line number the row starts at
+    pub line: usize,
+    pub fields: Vec<Field>,
+    /// Char offset in the file of the end of the row, excluding the line break
+    pub end: usize,
+}
+
+/// Splits `src` into rows and fields separated by `delimiter`.
+///
+/// Fields may be surrounded by `"`, in which case they can contain delimiters, line breaks
+/// and `""` for a literal quote. Empty lines are skipped.
+pub fn parse(src: &str, delimiter: char) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let mut chars = src.chars().enumerate().peekable();
+    let mut line = 1;
+
+    while let Some(&(start, _)) = chars.peek() {
+        let row_line = line;
+        let mut fields = Vec::new();
+        let mut field = Field {
+            text: String::new(),
+            offsets: Vec::new(),
+            end: start,
+        };
+        let mut quoted = false;
+        let mut end = start;
+
+        while let Some((i, c)) = chars.next() {
+            if quoted {
+                match c {
+                    '"' if chars.peek().map(|&(_, c)| c) == Some('"') => {
+                        chars.next();
+                        field.text.push('"');
+                        field.offsets.push(i);
+                    }
+                    '"' => quoted = false,
+                    c => {
+                        if c == '\n' {
+                            line += 1;
+                        }
+                        field.text.push(c);
+                        field.offsets.push(i);
+                    }
+                }
+            } else {
+                match c {
+                    '\n' => {
+                        line += 1;
+                        break;
+                    }
+                    '\r' if chars.peek().map(|&(_, c)| c) == Some('\n') => continue,
+                    '"' if field.text.is_empty() => quoted = true,
+                    c if c == delimiter => {
+                        let next = Field {
+                            text: String::new(),
+                            offsets: Vec::new(),
+                            end: i + 1,
+                        };
+                        fields.push(std::mem::replace(&mut field, next));
+                    }
+                    c => {
+                        field.text.push(c);
+                        field.offsets.push(i);
+                    }
+                }
+            }
+
+            end = i + 1;
+            if c != delimiter || quoted {
+                field.end = i + 1;
+            }
+        }
+
+        // Skip empty lines
+        if end == start {
+            continue;
+        }
+
+        fields.push(field);
+        rows.push(Row {
+            line: row_line,
+            fields,
+            end,
+        });
+    }
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(rows: &[Row]) -> Vec<Vec<&str>> {
+        rows.iter()
+            .map(|row| row.fields.iter().map(|f| f.text.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let src = "date,name\n2022/03/19,a\n\n2021/06/10,\"b, c\"\r\n";
+        let rows = parse(src, ',');
+
+        assert_eq!(
+            texts(&rows),
+            vec![
+                vec!["date", "name"],
+                vec!["2022/03/19", "a"],
+                vec!["2021/06/10", "b, c"],
+            ]
+        );
+        assert_eq!(
+            rows.iter().map(|r| r.line).collect::<Vec<_>>(),
+            vec![1, 2, 4]
+        );
+        assert_eq!(rows[1].fields[0].offsets[0], 10);
+        assert_eq!(rows[2].fields[1].offsets[0], 36);
+        assert_eq!(rows[2].end, 41);
+    }
+
+    #[test]
+    fn test_parse_quotes() {
+        let src = "\"a\"\"b\"\t\"x\ny\"\t\"\"";
+        let rows = parse(src, '\t');
+
+        assert_eq!(texts(&rows), vec![vec!["a\"b", "x\ny", ""]]);
+        assert_eq!(rows[0].fields[0].offsets, vec![1, 2, 4]);
+        assert_eq!(rows[0].fields[1].offsets, vec![8, 9, 10]);
+    }
+
+    #[test]
+    fn test_spanned_chars() {
+        let rows = parse("x,\"1\"\"2\"", ',');
+        assert_eq!(
+            rows[0].fields[1].spanned_chars().collect::<Vec<_>>(),
+            vec![('1', 3..4), ('"', 4..5), ('2', 6..7)]
+        );
+    }
+}
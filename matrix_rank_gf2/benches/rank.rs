@@ -0,0 +1,45 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use matrix_rank_gf2::{
+    bit_matrix::{BitMatrix, m4ri_k},
+    matrix_rank,
+    rng::{Generator, SplitMix64},
+};
+
+/// High halves of SplitMix64, whose output is not linear over GF(2) so that random
+/// matrices are full rank as often as expected. A xorshift would give rank at most its
+/// state size.
+fn splitmix64(state: u64) -> impl FnMut() -> u32 {
+    let mut generator = SplitMix64::new(state);
+    move || (generator.next() >> 32) as u32
+}
+
+fn random_matrix(rows: usize, cols: usize) -> BitMatrix {
+    let mut next = splitmix64(0x1234_5678);
+    BitMatrix::from_u32s(rows, cols, &mut std::iter::from_fn(|| Some(next()))).unwrap()
+}
+
+fn bench_rank(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rank");
+
+    // The original `[u32]` implementation only handles 32 columns
+    let mut next = splitmix64(0x1234_5678);
+    let rows = (0..32).map(|_| next()).collect::<Vec<_>>();
+    group.bench_function("matrix_rank/32", |b| {
+        b.iter(|| matrix_rank(&mut rows.clone()))
+    });
+
+    for n in [32, 64, 256, 1024] {
+        let m = random_matrix(n, n);
+        group.bench_with_input(BenchmarkId::new("gauss", n), &m, |b, m| {
+            b.iter(|| m.clone().rank_gauss())
+        });
+        group.bench_with_input(BenchmarkId::new("m4ri", n), &m, |b, m| {
+            b.iter(|| m.clone().rank_m4ri(m4ri_k(n)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_rank);
+criterion_main!(benches);
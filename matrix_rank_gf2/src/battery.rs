@@ -0,0 +1,547 @@
+//! A small battery of statistical tests in the spirit of TestU01's SmallCrush.
+//!
+//! Each test reads 32-bit values from a generator and reduces them to a single p-value.
+//! Bits of a value are used from the lowest one, as `BitMatrix::from_u32s` does.
+
+use std::{io::Write, time::Instant};
+
+use serde::Serialize;
+
+use crate::{
+    bit_matrix::BitMatrix,
+    rank_distribution,
+    stats::{ChiSquare, MIN_EXPECTED, Verdict, chi_square, erfc, poisson_sf},
+};
+
+pub trait StatTest {
+    fn name(&self) -> String;
+
+    /// Runs the test on `values` and returns its p-value, or `None` if `values` ran out or
+    /// gave too little data to test.
+    fn run(&self, values: &mut dyn Iterator<Item = u32>) -> Option<f64>;
+}
+
+/// p-value of `result`, or `None` if all classes merged into one and left nothing to test.
+fn p_value(result: ChiSquare) -> Option<f64> {
+    (result.dof > 0).then_some(result.p_value)
+}
+
+/// The first `n` bits of `values`, or `None` if `values` runs out.
+fn bits(values: &mut dyn Iterator<Item = u32>, n: usize) -> Option<Vec<bool>> {
+    let mut bits = Vec::with_capacity(n);
+    while bits.len() < n {
+        let value = values.next()?;
+        bits.extend((0..32).map(|i| (value >> i) & 1 == 1).take(n - bits.len()));
+    }
+    Some(bits)
+}
+
+/// Length of the shortest LFSR generating `bits`, by the Berlekamp–Massey algorithm.
+pub fn linear_complexity(bits: &[bool]) -> usize {
+    let n = bits.len();
+    let mut c = vec![false; n + 1];
+    let mut b = vec![false; n + 1];
+    c[0] = true;
+    b[0] = true;
+    let (mut l, mut m) = (0, 1);
+
+    for i in 0..n {
+        let discrepancy = (1..=l).fold(bits[i], |d, j| d ^ (c[j] & bits[i - j]));
+        if !discrepancy {
+            m += 1;
+            continue;
+        }
+
+        let t = c.clone();
+        for j in 0..=n - m {
+            c[j + m] ^= b[j];
+        }
+        if 2 * l <= i {
+            l = i + 1 - l;
+            b = t;
+            m = 1;
+        } else {
+            m += 1;
+        }
+    }
+    l
+}
+
+/// Frequency test of NIST SP 800-22: the numbers of ones and zeros should be close.
+pub struct Monobit {
+    pub bits: usize,
+}
+
+impl StatTest for Monobit {
+    fn name(&self) -> String {
+        "Monobit".to_string()
+    }
+
+    fn run(&self, values: &mut dyn Iterator<Item = u32>) -> Option<f64> {
+        let bits = bits(values, self.bits)?;
+        let sum = bits
+            .iter()
+            .map(|&b| if b { 1.0 } else { -1.0 })
+            .sum::<f64>();
+        Some(erfc(sum.abs() / (2.0 * self.bits as f64).sqrt()))
+    }
+}
+
+/// Runs test of NIST SP 800-22: the number of runs of identical bits should be as expected.
+pub struct Runs {
+    pub bits: usize,
+}
+
+impl StatTest for Runs {
+    fn name(&self) -> String {
+        "Runs".to_string()
+    }
+
+    fn run(&self, values: &mut dyn Iterator<Item = u32>) -> Option<f64> {
+        let bits = bits(values, self.bits)?;
+        let n = self.bits as f64;
+        let pi = bits.iter().filter(|&&b| b).count() as f64 / n;
+        // The test is meaningless when the frequency test already fails
+        if (pi - 0.5).abs() >= 2.0 / n.sqrt() {
+            return Some(0.0);
+        }
+
+        let runs = 1 + bits.windows(2).filter(|w| w[0] != w[1]).count();
+        let mean = 2.0 * n * pi * (1.0 - pi);
+        Some(erfc(
+            (runs as f64 - mean).abs() / (2.0 * (2.0 * n).sqrt() * pi * (1.0 - pi)),
+        ))
+    }
+}
+
+/// Serial test on pairs of non-overlapping `bits`-bit values taken from the top of each
+/// output, whose `2^(2 bits)` combinations should be equally likely.
+pub struct Serial {
+    pub bits: u32,
+    pub pairs: usize,
+}
+
+impl StatTest for Serial {
+    fn name(&self) -> String {
+        format!("Serial (d = {})", self.bits)
+    }
+
+    fn run(&self, values: &mut dyn Iterator<Item = u32>) -> Option<f64> {
+        let cells = 1 << (2 * self.bits);
+        let mut counts = vec![0; cells];
+        for _ in 0..self.pairs {
+            let a = values.next()? >> (32 - self.bits);
+            let b = values.next()? >> (32 - self.bits);
+            counts[((a << self.bits) | b) as usize] += 1;
+        }
+        let probabilities = vec![1.0 / cells as f64; cells];
+        p_value(chi_square(&counts, &probabilities, MIN_EXPECTED))
+    }
+}
+
+/// Gap test: lengths of the gaps between outputs falling in `[alpha, beta)`, as fractions
+/// of 2^32, should be geometrically distributed.
+pub struct Gap {
+    pub alpha: f64,
+    pub beta: f64,
+    pub gaps: usize,
+}
+
+impl StatTest for Gap {
+    fn name(&self) -> String {
+        format!("Gap [{}, {})", self.alpha, self.beta)
+    }
+
+    fn run(&self, values: &mut dyn Iterator<Item = u32>) -> Option<f64> {
+        let p = self.beta - self.alpha;
+        // Longer gaps are lumped into the last class, expecting about MIN_EXPECTED of them
+        let classes = ((MIN_EXPECTED / self.gaps as f64).ln() / (1.0 - p).ln())
+            .ceil()
+            .max(1.0) as usize;
+
+        let mut counts = vec![0; classes + 1];
+        for _ in 0..self.gaps {
+            let mut gap = 0;
+            loop {
+                let u = values.next()? as f64 / 2f64.powi(32);
+                if (self.alpha..self.beta).contains(&u) {
+                    break;
+                }
+                gap += 1;
+            }
+            counts[gap.min(classes)] += 1;
+        }
+
+        let mut probabilities = (0..classes)
+            .map(|j| p * (1.0 - p).powi(j as i32))
+            .collect::<Vec<_>>();
+        probabilities.push((1.0 - p).powi(classes as i32));
+        p_value(chi_square(&counts, &probabilities, MIN_EXPECTED))
+    }
+}
+
+/// Birthday spacings test of Marsaglia: `n` birthdays in a year of `2^bits` days taken
+/// from the top of each output, repeated `repeat` times.
+///
+/// The number of equal spacings between sorted birthdays is approximately Poisson with
+/// mean `n^3 / (4 * 2^bits)` per repetition, and the p-value is its right tail.
+pub struct BirthdaySpacings {
+    pub n: usize,
+    pub bits: u32,
+    pub repeat: usize,
+}
+
+impl StatTest for BirthdaySpacings {
+    fn name(&self) -> String {
+        format!("BirthdaySpacings (n = {}, d = 2^{})", self.n, self.bits)
+    }
+
+    fn run(&self, values: &mut dyn Iterator<Item = u32>) -> Option<f64> {
+        let mut collisions = 0;
+        for _ in 0..self.repeat {
+            let mut birthdays = (0..self.n)
+                .map(|_| values.next().map(|v| v >> (32 - self.bits)))
+                .collect::<Option<Vec<_>>>()?;
+            birthdays.sort_unstable();
+            let mut spacings = birthdays
+                .windows(2)
+                .map(|w| w[1] - w[0])
+                .collect::<Vec<_>>();
+            spacings.sort_unstable();
+            collisions += spacings.windows(2).filter(|w| w[0] == w[1]).count();
+        }
+
+        let lambda = (self.n as f64).powi(3) / (4.0 * 2f64.powi(self.bits as i32));
+        Some(poisson_sf(collisions, lambda * self.repeat as f64))
+    }
+}
+
+/// Linear complexity test of NIST SP 800-22 on `blocks` blocks of `block` bits.
+pub struct LinearComplexity {
+    pub block: usize,
+    pub blocks: usize,
+}
+
+impl StatTest for LinearComplexity {
+    fn name(&self) -> String {
+        format!("LinearComplexity (M = {})", self.block)
+    }
+
+    fn run(&self, values: &mut dyn Iterator<Item = u32>) -> Option<f64> {
+        const PROBABILITIES: [f64; 7] = [0.010417, 0.03125, 0.125, 0.5, 0.25, 0.0625, 0.020833];
+
+        let m = self.block as f64;
+        let sign = if self.block.is_multiple_of(2) {
+            1.0
+        } else {
+            -1.0
+        };
+        let mean = m / 2.0 + (9.0 - sign) / 36.0 - (m / 3.0 + 2.0 / 9.0) / m.exp2();
+
+        let mut counts = [0; 7];
+        for _ in 0..self.blocks {
+            let l = linear_complexity(&bits(values, self.block)?) as f64;
+            let t = sign * (l - mean) + 2.0 / 9.0;
+            let class = if t <= -2.5 {
+                0
+            } else if t >= 2.5 {
+                6
+            } else {
+                (t + 3.5) as usize
+            };
+            counts[class] += 1;
+        }
+        p_value(chi_square(&counts, &PROBABILITIES, 0.0))
+    }
+}
+
+/// Chi-square test of the ranks of `rows` x `cols` matrices.
+pub struct MatrixRank {
+    pub rows: usize,
+    pub cols: usize,
+    pub matrices: usize,
+}
+
+impl StatTest for MatrixRank {
+    fn name(&self) -> String {
+        format!("MatrixRank ({} x {})", self.rows, self.cols)
+    }
+
+    fn run(&self, values: &mut dyn Iterator<Item = u32>) -> Option<f64> {
+        let mut counts = vec![0; self.rows.min(self.cols) + 1];
+        for _ in 0..self.matrices {
+            counts[BitMatrix::from_u32s(self.rows, self.cols, values)?.rank()] += 1;
+        }
+        let expected = rank_distribution(self.rows, self.cols);
+        p_value(chi_square(&counts, &expected, MIN_EXPECTED))
+    }
+}
+
+/// Default battery, consuming about 2^23 outputs, 3.2 million of which by the rank test
+/// of 32 x 32 matrices.
+pub fn standard() -> Vec<Box<dyn StatTest>> {
+    vec![
+        Box::new(BirthdaySpacings {
+            n: 1 << 12,
+            bits: 32,
+            repeat: 100,
+        }),
+        Box::new(Monobit { bits: 1 << 24 }),
+        Box::new(Runs { bits: 1 << 24 }),
+        Box::new(Serial {
+            bits: 4,
+            pairs: 1 << 20,
+        }),
+        Box::new(Gap {
+            alpha: 0.0,
+            beta: 1.0 / 16.0,
+            gaps: 1 << 16,
+        }),
+        Box::new(LinearComplexity {
+            block: 500,
+            blocks: 1000,
+        }),
+        Box::new(MatrixRank {
+            rows: 32,
+            cols: 32,
+            matrices: 100_000,
+        }),
+        Box::new(MatrixRank {
+            rows: 300,
+            cols: 300,
+            matrices: 200,
+        }),
+    ]
+}
+
+/// p-value of each of `tests`, run one after another on `values`.
+pub fn run(
+    tests: &[Box<dyn StatTest>],
+    values: &mut dyn Iterator<Item = u32>,
+) -> Vec<(String, Option<f64>)> {
+    tests
+        .iter()
+        .map(|test| (test.name(), test.run(values)))
+        .collect()
+}
+
+/// Result of one test of a battery, as written by `--json`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TestResult {
+    pub generator: String,
+    pub test: String,
+    /// `None` if the input ran out or gave too little data to test
+    pub p_value: Option<f64>,
+    pub verdict: String,
+}
+
+fn verdict(p_value: Option<f64>) -> Verdict {
+    p_value.map_or(Verdict::InsufficientData, Verdict::of)
+}
+
+/// `results` of `run` on the generator called `generator`.
+pub fn results(generator: &str, results: &[(String, Option<f64>)]) -> Vec<TestResult> {
+    results
+        .iter()
+        .map(|(test, p_value)| TestResult {
+            generator: generator.to_string(),
+            test: test.clone(),
+            p_value: *p_value,
+            verdict: verdict(*p_value).to_string(),
+        })
+        .collect()
+}
+
+/// Prints `results` in the layout of TestU01's summaries.
+pub fn summary(
+    mut w: impl Write,
+    generator: &str,
+    results: &[(String, Option<f64>)],
+    started: Instant,
+) -> std::io::Result<()> {
+    writeln!(w, "========= Summary results of battery =========")?;
+    writeln!(w)?;
+    writeln!(w, " Generator:        {}", generator)?;
+    writeln!(w, " Number of tests:  {}", results.len())?;
+    writeln!(w, " Total time:       {:.2?}", started.elapsed())?;
+    writeln!(w)?;
+    writeln!(w, "       Test                                  p-value")?;
+    writeln!(
+        w,
+        " -------------------------------------------------------------"
+    )?;
+
+    let mut failed = 0;
+    for (i, (name, p_value)) in results.iter().enumerate() {
+        let verdict = verdict(*p_value);
+        if verdict != Verdict::Pass {
+            failed += 1;
+        }
+        let p_value = p_value.map_or("-".to_string(), |p| format!("{:.4e}", p));
+        writeln!(w, " {:>2}  {:<38}{:>11}  {}", i + 1, name, p_value, verdict)?;
+    }
+
+    writeln!(
+        w,
+        " -------------------------------------------------------------"
+    )?;
+    if failed == 0 {
+        writeln!(w, " All tests were passed")?;
+    } else {
+        writeln!(w, " {} of {} tests were not passed", failed, results.len())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{Generator, Lcg, SplitMix64, Xorshift32};
+
+    fn values(mut generator: impl Generator) -> impl Iterator<Item = u32> {
+        std::iter::from_fn(move || Some(generator.next() as u32))
+    }
+
+    /// Packs a string of `0` and `1` into values, the first character being the lowest bit.
+    fn from_bits(s: &str) -> Vec<u32> {
+        let bits = s.bytes().map(|b| b == b'1').collect::<Vec<_>>();
+        bits.chunks(32)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0, |v, (i, &b)| v | (b as u32) << i)
+            })
+            .collect()
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_linear_complexity() {
+        // Example of NIST SP 800-22 2.10.4
+        let bits = "1101011110001"
+            .bytes()
+            .map(|b| b == b'1')
+            .collect::<Vec<_>>();
+        assert_eq!(linear_complexity(&bits), 4);
+        assert_eq!(linear_complexity(&[false; 10]), 0);
+        assert_eq!(linear_complexity(&[false, false, false, true]), 4);
+        // Period 3 sequence generated by s[i] = s[i - 1] ^ s[i - 2]
+        assert_eq!(
+            linear_complexity(&[true, true, false, true, true, false, true]),
+            2
+        );
+
+        // Any bit of a xorshift is an LFSR sequence of at most its state size
+        let bits = values(Xorshift32::new(1))
+            .map(|v| v & 1 == 1)
+            .take(200)
+            .collect::<Vec<_>>();
+        assert_eq!(linear_complexity(&bits), 32);
+    }
+
+    #[test]
+    fn test_nist_examples() {
+        // Examples of NIST SP 800-22 2.1.4 and 2.3.4
+        let p = Monobit { bits: 10 }.run(&mut from_bits("1011010101").into_iter());
+        assert_close(p.unwrap(), 0.527089);
+        let p = Runs { bits: 10 }.run(&mut from_bits("1001101011").into_iter());
+        assert_close(p.unwrap(), 0.147232);
+    }
+
+    #[test]
+    fn test_short_input() {
+        for test in standard() {
+            assert_eq!(test.run(&mut (0..1000).map(|_| 1)), None, "{}", test.name());
+        }
+    }
+
+    #[test]
+    fn test_good_and_bad_generators() {
+        let tests: Vec<Box<dyn StatTest>> = vec![
+            Box::new(BirthdaySpacings {
+                n: 1 << 10,
+                bits: 24,
+                repeat: 20,
+            }),
+            Box::new(Monobit { bits: 1 << 16 }),
+            Box::new(Runs { bits: 1 << 16 }),
+            Box::new(Serial {
+                bits: 3,
+                pairs: 1 << 14,
+            }),
+            Box::new(Gap {
+                alpha: 0.0,
+                beta: 0.25,
+                gaps: 1 << 12,
+            }),
+            Box::new(LinearComplexity {
+                block: 64,
+                blocks: 100,
+            }),
+            Box::new(MatrixRank {
+                rows: 32,
+                cols: 32,
+                matrices: 2000,
+            }),
+        ];
+
+        for (name, p_value) in run(&tests, &mut values(SplitMix64::new(1))) {
+            assert_eq!(Verdict::of(p_value.unwrap()), Verdict::Pass, "{}", name);
+        }
+
+        // Matrices of outputs of a linear generator have ranks at most its state size
+        assert!(tests[6].run(&mut values(Xorshift32::new(1))).unwrap() < 1e-10);
+
+        // Bit k of an LCG modulo a power of two has a period of at most 2^(k + 1)
+        assert!(tests[6].run(&mut values(Lcg::randu(1))).unwrap() < 1e-10);
+
+        // So few matrices merge all ranks into one class
+        let few = MatrixRank {
+            rows: 32,
+            cols: 32,
+            matrices: 5,
+        };
+        assert_eq!(few.run(&mut values(SplitMix64::new(1))), None);
+    }
+
+    #[test]
+    fn test_summary() {
+        let results = vec![
+            ("Monobit".to_string(), Some(0.5)),
+            ("Runs".to_string(), Some(1e-12)),
+            ("Gap".to_string(), None),
+        ];
+        let mut buf = Vec::new();
+        summary(&mut buf, "splitmix64", &results, Instant::now()).unwrap();
+
+        let out = String::from_utf8(buf).unwrap();
+        assert!(out.contains(" Generator:        splitmix64"));
+        assert!(out.contains("  1  Monobit"));
+        assert!(out.contains("1.0000e-12  FAIL"));
+        assert!(out.contains("INSUFFICIENT DATA"));
+        assert!(out.contains(" 2 of 3 tests were not passed"));
+    }
+
+    #[test]
+    fn test_results() {
+        let results = results(
+            "splitmix64",
+            &[
+                ("Monobit".to_string(), Some(0.5)),
+                ("Gap".to_string(), None),
+            ],
+        );
+        assert_eq!(results[0].generator, "splitmix64");
+        assert_eq!(results[0].verdict, "PASS");
+        assert_eq!(results[1].test, "Gap");
+        assert_eq!(results[1].verdict, "INSUFFICIENT DATA");
+        assert_eq!(
+            serde_json::to_string(&results[1]).unwrap(),
+            r#"{"generator":"splitmix64","test":"Gap","p_value":null,"verdict":"INSUFFICIENT DATA"}"#
+        );
+    }
+}
@@ -0,0 +1,691 @@
+/// Dense matrix over GF(2) whose rows are packed into `u64` words.
+///
+/// Bit `c` of a row is bit `c % 64` of its word `c / 64`, and bits past `cols` are kept zero.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitMatrix {
+    rows: usize,
+    cols: usize,
+    words: usize,
+    data: Vec<u64>,
+}
+
+impl BitMatrix {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        let words = cols.div_ceil(64);
+        Self {
+            rows,
+            cols,
+            words,
+            data: vec![0; rows * words],
+        }
+    }
+
+    /// Builds a `rows` x `cols` matrix from 32-bit outputs of a generator.
+    ///
+    /// Each row takes `cols.div_ceil(32)` consecutive values, the first one giving the
+    /// lowest bits, and the bits past `cols` are dropped.
+    /// Returns `None` if `values` runs out.
+    pub fn from_u32s(
+        rows: usize,
+        cols: usize,
+        values: &mut (impl Iterator<Item = u32> + ?Sized),
+    ) -> Option<Self> {
+        Self::from_bits(rows, cols, 32, values)
+    }
+
+    /// Same as `from_u32s`, but each value only gives its low `bits` bits, so that a row
+    /// takes `cols.div_ceil(bits)` values.
+    pub fn from_bits(
+        rows: usize,
+        cols: usize,
+        bits: u32,
+        values: &mut (impl Iterator<Item = u32> + ?Sized),
+    ) -> Option<Self> {
+        assert!((1..=32).contains(&bits));
+        let bits = bits as usize;
+        let mask = u64::MAX >> (64 - bits);
+
+        let mut matrix = Self::zeros(rows, cols);
+        for r in 0..rows {
+            let row = matrix.row_mut(r);
+            for c in (0..cols).step_by(bits) {
+                let value = values.next()? as u64 & mask;
+                row[c / 64] |= value << (c % 64);
+                if c % 64 + bits > 64 && c / 64 + 1 < row.len() {
+                    row[c / 64 + 1] |= value >> (64 - c % 64);
+                }
+            }
+            matrix.mask_row(r);
+        }
+        Some(matrix)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Number of `u64` words per row.
+    pub fn words(&self) -> usize {
+        self.words
+    }
+
+    pub fn row(&self, r: usize) -> &[u64] {
+        &self.data[r * self.words..(r + 1) * self.words]
+    }
+
+    pub fn row_mut(&mut self, r: usize) -> &mut [u64] {
+        &mut self.data[r * self.words..(r + 1) * self.words]
+    }
+
+    /// Clears the bits of row `r` past `cols`.
+    fn mask_row(&mut self, r: usize) {
+        if !self.cols.is_multiple_of(64) {
+            let last = self.words - 1;
+            let mask = (1u64 << (self.cols % 64)) - 1;
+            self.row_mut(r)[last] &= mask;
+        }
+    }
+
+    pub fn get(&self, r: usize, c: usize) -> bool {
+        assert!(r < self.rows && c < self.cols);
+        (self.data[r * self.words + c / 64] >> (c % 64)) & 1 == 1
+    }
+
+    pub fn set(&mut self, r: usize, c: usize, value: bool) {
+        assert!(r < self.rows && c < self.cols);
+        let word = &mut self.data[r * self.words + c / 64];
+        if value {
+            *word |= 1 << (c % 64);
+        } else {
+            *word &= !(1 << (c % 64));
+        }
+    }
+
+    pub fn swap_rows(&mut self, a: usize, b: usize) {
+        if a != b {
+            for i in 0..self.words {
+                self.data.swap(a * self.words + i, b * self.words + i);
+            }
+        }
+    }
+
+    /// `row[dst] ^= row[src]`, only touching words from `from_word` on.
+    fn xor_row(&mut self, dst: usize, src: usize, from_word: usize) {
+        let words = self.words;
+        let range = from_word..words;
+        if dst > src {
+            let (head, tail) = self.data.split_at_mut(dst * words);
+            xor_into(&mut tail[range.clone()], &head[src * words..][range]);
+        } else {
+            let (head, tail) = self.data.split_at_mut(src * words);
+            xor_into(&mut head[dst * words..][range.clone()], &tail[range]);
+        }
+    }
+
+    /// `n <= 64` bits of row `r` starting from column `c`, as an integer.
+    fn bits(&self, r: usize, c: usize, n: usize) -> u64 {
+        let row = self.row(r);
+        let (w, shift) = (c / 64, c % 64);
+        let mut bits = row[w] >> shift;
+        if shift != 0 && w + 1 < self.words {
+            bits |= row[w + 1] << (64 - shift);
+        }
+        if n < 64 { bits & ((1 << n) - 1) } else { bits }
+    }
+
+    /// Rank, computed by `rank_m4ri` for large matrices and by `rank_gauss` otherwise.
+    /// The matrix is left in row echelon form.
+    pub fn rank(&mut self) -> usize {
+        if self.rows.min(self.cols) >= M4RI_THRESHOLD {
+            self.rank_m4ri(m4ri_k(self.rows))
+        } else {
+            self.rank_gauss()
+        }
+    }
+
+    /// Rank by Gaussian elimination, XORing whole `u64` words at once.
+    pub fn rank_gauss(&mut self) -> usize {
+        let words = self.words;
+        let mut rank = 0;
+        for c in 0..self.cols {
+            if rank == self.rows {
+                break;
+            }
+            let (w, mask) = (c / 64, 1 << (c % 64));
+            let Some(pivot) = (rank..self.rows).find(|&r| self.data[r * words + w] & mask != 0)
+            else {
+                continue;
+            };
+            self.swap_rows(rank, pivot);
+            for r in rank + 1..self.rows {
+                if self.data[r * words + w] & mask != 0 {
+                    self.xor_row(r, rank, w);
+                }
+            }
+            rank += 1;
+        }
+        rank
+    }
+
+    /// Rank by the Method of Four Russians, eliminating `k` columns at a time.
+    ///
+    /// For each block of `k` columns, up to `k` pivot rows are found and reduced against
+    /// each other, then all of their `2^k` linear combinations are tabulated so that each
+    /// remaining row is cleared by a single row XOR instead of up to `k`.
+    pub fn rank_m4ri(&mut self, k: usize) -> usize {
+        assert!((1..=16).contains(&k));
+
+        let mut rank = 0;
+        let mut c = 0;
+        let mut table = Vec::new();
+
+        while c < self.cols && rank < self.rows {
+            let n = k.min(self.cols - c);
+            let w = c / 64;
+
+            // Pivot columns, relative to `c`, of rows `rank..rank + pivots.len()`
+            let mut pivots = Vec::with_capacity(n);
+            for j in 0..n {
+                let found = (rank + pivots.len()..self.rows).find(|&r| {
+                    let mut bits = self.bits(r, c, n);
+                    for (t, &p) in pivots.iter().enumerate() {
+                        if (bits >> p) & 1 == 1 {
+                            bits ^= self.bits(rank + t, c, n);
+                        }
+                    }
+                    (bits >> j) & 1 == 1
+                });
+                let Some(found) = found else {
+                    continue;
+                };
+
+                let row = rank + pivots.len();
+                self.swap_rows(row, found);
+                for (t, &p) in pivots.iter().enumerate() {
+                    if (self.bits(row, c, n) >> p) & 1 == 1 {
+                        self.xor_row(row, rank + t, w);
+                    }
+                }
+                // Keep the pivot rows reduced so that a row is cleared by the combination
+                // of pivots selected by its bits at the pivot columns
+                for t in 0..pivots.len() {
+                    if (self.bits(rank + t, c, n) >> j) & 1 == 1 {
+                        self.xor_row(rank + t, row, w);
+                    }
+                }
+                pivots.push(j);
+            }
+
+            if pivots.is_empty() {
+                c += n;
+                continue;
+            }
+
+            // table[mask] = XOR of pivot rows selected by `mask`, built in Gray code order
+            let width = self.words - w;
+            table.clear();
+            table.resize(width << pivots.len(), 0);
+            for mask in 1usize..1 << pivots.len() {
+                let t = mask.trailing_zeros() as usize;
+                let (done, rest) = table.split_at_mut(mask * width);
+                let entry = &mut rest[..width];
+                entry.copy_from_slice(&done[(mask & (mask - 1)) * width..][..width]);
+                xor_into(entry, &self.row(rank + t)[w..]);
+            }
+
+            let below = rank + pivots.len();
+            for r in below..self.rows {
+                let bits = self.bits(r, c, n);
+                let mask = pivots
+                    .iter()
+                    .enumerate()
+                    .fold(0, |mask, (t, &p)| mask | (((bits >> p) & 1) as usize) << t);
+                if mask != 0 {
+                    xor_into(&mut self.row_mut(r)[w..], &table[mask * width..][..width]);
+                }
+            }
+
+            rank = below;
+            c += n;
+        }
+        rank
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut matrix = Self::zeros(n, n);
+        for i in 0..n {
+            matrix.set(i, i, true);
+        }
+        matrix
+    }
+
+    /// Matrix whose rows are the packed vectors `rows`, each of `cols` bits.
+    pub fn from_rows(cols: usize, rows: &[Vec<u64>]) -> Self {
+        let mut matrix = Self::zeros(rows.len(), cols);
+        for (r, row) in rows.iter().enumerate() {
+            matrix.row_mut(r).copy_from_slice(row);
+            matrix.mask_row(r);
+        }
+        matrix
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut matrix = Self::zeros(self.cols, self.rows);
+        for r in 0..self.rows {
+            for c in ones(self.row(r)) {
+                matrix.set(c, r, true);
+            }
+        }
+        matrix
+    }
+
+    /// `self * x` for a packed column vector `x` of `cols` bits.
+    pub fn mul_vec(&self, x: &[u64]) -> Vec<u64> {
+        assert_eq!(x.len(), self.words);
+        let mut y = vec![0; self.rows.div_ceil(64)];
+        for r in 0..self.rows {
+            let parity = self
+                .row(r)
+                .iter()
+                .zip(x)
+                .fold(0, |p, (a, b)| p ^ (a & b).count_ones());
+            y[r / 64] |= ((parity & 1) as u64) << (r % 64);
+        }
+        y
+    }
+
+    /// `self^e` of a square matrix, by repeated squaring.
+    pub fn pow(&self, mut e: u128) -> Self {
+        assert_eq!(self.rows, self.cols);
+        let mut result = Self::identity(self.rows);
+        let mut base = self.clone();
+        while e > 0 {
+            if e & 1 == 1 {
+                result = &result * &base;
+            }
+            e >>= 1;
+            if e > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    /// Brings the first `limit` columns into reduced row echelon form, applying the same
+    /// row operations to the remaining columns. Returns the pivot columns.
+    fn reduce(&mut self, limit: usize) -> Vec<usize> {
+        let mut pivots = Vec::new();
+        for c in 0..limit {
+            let rank = pivots.len();
+            if rank == self.rows {
+                break;
+            }
+            let Some(pivot) = (rank..self.rows).find(|&r| self.get(r, c)) else {
+                continue;
+            };
+            self.swap_rows(rank, pivot);
+            for r in 0..self.rows {
+                if r != rank && self.get(r, c) {
+                    self.xor_row(r, rank, c / 64);
+                }
+            }
+            pivots.push(c);
+        }
+        pivots
+    }
+
+    /// `[self | other]`
+    fn augment(&self, other: &Self) -> Self {
+        assert_eq!(self.rows, other.rows);
+        let mut matrix = Self::zeros(self.rows, self.cols + other.cols);
+        for r in 0..self.rows {
+            matrix.row_mut(r)[..self.words].copy_from_slice(self.row(r));
+            for c in ones(other.row(r)) {
+                matrix.set(r, self.cols + c, true);
+            }
+        }
+        matrix
+    }
+
+    /// Columns `from..` of `self`.
+    fn columns_from(&self, from: usize) -> Self {
+        let mut matrix = Self::zeros(self.rows, self.cols - from);
+        for r in 0..self.rows {
+            for c in ones(self.row(r)).filter(|&c| c >= from) {
+                matrix.set(r, c - from, true);
+            }
+        }
+        matrix
+    }
+
+    /// Inverse of a square matrix, or `None` if it is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        assert_eq!(self.rows, self.cols);
+        let mut augmented = self.augment(&Self::identity(self.rows));
+        if augmented.reduce(self.cols).len() < self.rows {
+            return None;
+        }
+        Some(augmented.columns_from(self.cols))
+    }
+
+    /// Basis of the vectors `x` with `self * x = 0`, packed like rows.
+    pub fn kernel(&self) -> Vec<Vec<u64>> {
+        let mut reduced = self.clone();
+        let pivots = reduced.reduce(self.cols);
+
+        let mut is_pivot = vec![false; self.cols];
+        for &c in &pivots {
+            is_pivot[c] = true;
+        }
+
+        (0..self.cols)
+            .filter(|&free| !is_pivot[free])
+            .map(|free| {
+                let mut x = vec![0; self.words];
+                x[free / 64] |= 1 << (free % 64);
+                for (r, &c) in pivots.iter().enumerate() {
+                    if reduced.get(r, free) {
+                        x[c / 64] |= 1 << (c % 64);
+                    }
+                }
+                x
+            })
+            .collect()
+    }
+
+    /// A solution `x` of `self * x = b`, or `None` if there is none. Other solutions are
+    /// `x` plus any combination of `kernel()`.
+    pub fn solve(&self, b: &[u64]) -> Option<Vec<u64>> {
+        let mut column = Self::zeros(self.rows, 1);
+        for r in ones(b) {
+            column.set(r, 0, true);
+        }
+        let mut augmented = self.augment(&column);
+        let pivots = augmented.reduce(self.cols);
+
+        // A row reduced to zero must have a zero right hand side
+        if (pivots.len()..self.rows).any(|r| augmented.get(r, self.cols)) {
+            return None;
+        }
+        let mut x = vec![0; self.words];
+        for (r, &c) in pivots.iter().enumerate() {
+            if augmented.get(r, self.cols) {
+                x[c / 64] |= 1 << (c % 64);
+            }
+        }
+        Some(x)
+    }
+}
+
+impl std::ops::Mul for &BitMatrix {
+    type Output = BitMatrix;
+
+    fn mul(self, rhs: &BitMatrix) -> BitMatrix {
+        assert_eq!(self.cols, rhs.rows);
+        let mut product = BitMatrix::zeros(self.rows, rhs.cols);
+        for r in 0..self.rows {
+            for k in ones(self.row(r)) {
+                xor_into(product.row_mut(r), rhs.row(k));
+            }
+        }
+        product
+    }
+}
+
+/// Indices of the set bits of a packed vector.
+pub fn ones(x: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    x.iter().enumerate().flat_map(|(i, &word)| {
+        let mut word = word;
+        std::iter::from_fn(move || {
+            if word == 0 {
+                return None;
+            }
+            let bit = word.trailing_zeros() as usize;
+            word &= word - 1;
+            Some(i * 64 + bit)
+        })
+    })
+}
+
+/// Matrices with at least this many rows and columns use `BitMatrix::rank_m4ri`.
+pub const M4RI_THRESHOLD: usize = 256;
+
+/// Block size of `BitMatrix::rank_m4ri`, about `0.75 log2(rows)` as suggested by
+/// Albrecht et al. for the M4RI library.
+pub fn m4ri_k(rows: usize) -> usize {
+    ((rows.max(2).ilog2() as usize * 3) / 4).clamp(1, 8)
+}
+
+/// `dst ^= src` word by word, which compilers turn into SIMD instructions.
+fn xor_into(dst: &mut [u64], src: &[u64]) {
+    for (d, s) in dst.iter_mut().zip(src) {
+        *d ^= *s;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        matrix_rank,
+        rng::{Generator, SplitMix64, Xorshift32},
+    };
+
+    /// High halves of SplitMix64 from `seed`, as outputs of a xorshift would only span a
+    /// space of its state size.
+    fn splitmix64(seed: u64) -> impl FnMut() -> u32 {
+        let mut generator = SplitMix64::new(seed);
+        move || (generator.next() >> 32) as u32
+    }
+
+    #[test]
+    fn test_get_set() {
+        let mut m = BitMatrix::zeros(3, 130);
+        assert_eq!(m.words(), 3);
+        m.set(2, 129, true);
+        m.set(0, 64, true);
+        assert!(m.get(2, 129));
+        assert!(m.get(0, 64));
+        assert!(!m.get(1, 64));
+        m.set(2, 129, false);
+        assert!(!m.get(2, 129));
+    }
+
+    #[test]
+    fn test_from_u32s() {
+        let m = BitMatrix::from_u32s(2, 40, &mut [1, 0xffff_ffff, 2, 3].into_iter()).unwrap();
+        assert_eq!(m.row(0), &[1 | (0xff << 32)]);
+        assert_eq!(m.row(1), &[2 | (3 << 32)]);
+        assert!(BitMatrix::from_u32s(2, 40, &mut [1, 2, 3].into_iter()).is_none());
+    }
+
+    #[test]
+    fn test_from_bits() {
+        // 5 bits per value, the value giving columns 60..65 straddles two words
+        let mut values = (0..15).map(|i| if i == 12 { 0b10111 } else { 0b100000 });
+        let m = BitMatrix::from_bits(1, 66, 5, &mut values).unwrap();
+        assert_eq!(m.row(0), &[0b0111 << 60, 0b1]);
+        assert_eq!(values.next(), Some(0b100000));
+
+        let values = [0xdead_beef, 0x1234_5678, 0x0bad_f00d];
+        assert_eq!(
+            BitMatrix::from_bits(1, 70, 32, &mut values.into_iter()),
+            BitMatrix::from_u32s(1, 70, &mut values.into_iter())
+        );
+    }
+
+    #[test]
+    fn test_rank() {
+        let mut m = BitMatrix::from_u32s(3, 4, &mut [0b1100, 0b1010, 0b1001].into_iter()).unwrap();
+        assert_eq!(m.rank(), 3);
+        let mut m = BitMatrix::from_u32s(3, 4, &mut [0b1100, 0b1100, 0b1100].into_iter()).unwrap();
+        assert_eq!(m.rank(), 1);
+        let mut m = BitMatrix::zeros(3, 4);
+        assert_eq!(m.rank(), 0);
+
+        // Identity of size 100 with the first row duplicated into the last
+        let mut m = BitMatrix::zeros(101, 100);
+        for i in 0..100 {
+            m.set(i, i, true);
+        }
+        m.set(100, 0, true);
+        assert_eq!(m.rank(), 100);
+    }
+
+    #[test]
+    fn test_rank_matches_u32() {
+        let mut next = splitmix64(0x1234_5678);
+
+        for _ in 0..100 {
+            // Sparse rows so that rank deficient matrices are common
+            let mut rows = (0..32)
+                .map(|_| next() & next() & next())
+                .collect::<Vec<_>>();
+            let m = BitMatrix::from_u32s(32, 32, &mut rows.clone().into_iter()).unwrap();
+            let expected = matrix_rank(&mut rows);
+            assert_eq!(m.clone().rank(), expected);
+            assert_eq!(m.clone().rank_m4ri(m4ri_k(32)), expected);
+        }
+    }
+
+    #[test]
+    fn test_rank_methods_agree() {
+        let mut next = splitmix64(0);
+
+        for &(rows, cols) in &[
+            (1usize, 1usize),
+            (5, 3),
+            (3, 70),
+            (64, 64),
+            (65, 63),
+            (100, 130),
+            (130, 100),
+            (300, 257),
+        ] {
+            for sparsity in 0..4 {
+                let mut values = (0..rows * cols.div_ceil(32))
+                    .map(|_| (0..sparsity).fold(next(), |v, _| v & next()))
+                    .collect::<Vec<_>>();
+                // Duplicated rows make rank deficient matrices even when dense
+                if rows > 2 {
+                    let n = cols.div_ceil(32);
+                    values.copy_within(0..n, n);
+                }
+                let m = BitMatrix::from_u32s(rows, cols, &mut values.into_iter()).unwrap();
+
+                let expected = m.clone().rank_gauss();
+                for k in 1..=8 {
+                    assert_eq!(
+                        m.clone().rank_m4ri(k),
+                        expected,
+                        "{}x{} k = {}",
+                        rows,
+                        cols,
+                        k
+                    );
+                }
+                assert_eq!(m.clone().rank(), expected);
+            }
+        }
+    }
+
+    /// Transition matrix of `x ^= x << a` (or `>>` for negative `a`) on `n`-bit states.
+    fn xorshift(n: usize, a: i32) -> BitMatrix {
+        let mut matrix = BitMatrix::identity(n);
+        for r in 0..n {
+            let c = r as i64 - a as i64;
+            if (0..n as i64).contains(&c) {
+                matrix.set(r, c as usize, true);
+            }
+        }
+        matrix
+    }
+
+    fn random(rows: usize, cols: usize, seed: u64) -> BitMatrix {
+        let mut next = splitmix64(seed);
+        BitMatrix::from_u32s(rows, cols, &mut std::iter::from_fn(|| Some(next()))).unwrap()
+    }
+
+    #[test]
+    fn test_mul_transpose() {
+        let a = random(5, 70, 1);
+        let b = random(70, 9, 2);
+        let i = BitMatrix::identity(70);
+        assert_eq!(&a * &i, a);
+        assert_eq!(&i * &b, b);
+        assert_eq!(a.transpose().transpose(), a);
+        // (AB)^T = B^T A^T
+        assert_eq!((&a * &b).transpose(), &b.transpose() * &a.transpose());
+
+        let x = random(1, 70, 3).row(0).to_vec();
+        let column = BitMatrix::from_rows(70, std::slice::from_ref(&x)).transpose();
+        let product = (&a * &column).transpose();
+        assert_eq!(a.mul_vec(&x), product.row(0));
+    }
+
+    #[test]
+    fn test_inverse() {
+        let mut found = 0;
+        for seed in 0..20 {
+            let a = random(40, 40, seed);
+            match a.inverse() {
+                Some(inverse) => {
+                    assert_eq!(&a * &inverse, BitMatrix::identity(40));
+                    assert_eq!(&inverse * &a, BitMatrix::identity(40));
+                    found += 1;
+                }
+                None => assert!(a.clone().rank() < 40),
+            }
+        }
+        // About 29% of random square matrices are invertible
+        assert!(found > 0);
+        assert!(BitMatrix::zeros(3, 3).inverse().is_none());
+    }
+
+    #[test]
+    fn test_kernel_solve() {
+        for (rows, cols, seed) in [(10, 30, 1), (30, 10, 2), (70, 70, 3), (5, 130, 4)] {
+            // Duplicated rows make the system rank deficient
+            let mut a = random(rows, cols, seed);
+            let first = a.row(0).to_vec();
+            a.row_mut(rows - 1).copy_from_slice(&first);
+
+            let kernel = a.kernel();
+            assert_eq!(kernel.len(), cols - a.clone().rank());
+            for x in &kernel {
+                assert!(a.mul_vec(x).iter().all(|&w| w == 0));
+            }
+
+            let x = random(1, cols, seed + 100).row(0).to_vec();
+            let b = a.mul_vec(&x);
+            let solution = a.solve(&b).unwrap();
+            assert_eq!(a.mul_vec(&solution), b);
+
+            // Flipping the last equation contradicts the first one
+            let mut b = b;
+            b[(rows - 1) / 64] ^= 1 << ((rows - 1) % 64);
+            assert_eq!(a.solve(&b), None);
+        }
+    }
+
+    #[test]
+    fn test_xorshift32_period() {
+        let t = &(&xorshift(32, 5) * &xorshift(32, -17)) * &xorshift(32, 13);
+
+        // Jumping ahead by a matrix power
+        let mut generator = Xorshift32::new(1);
+        let state = (0..1000).map(|_| generator.next()).last().unwrap();
+        assert_eq!(t.pow(1000).mul_vec(&[1]), [state]);
+
+        // Full period 2^32 - 1, whose prime factors are 3, 5, 17, 257 and 65537
+        let period = (1u128 << 32) - 1;
+        assert_eq!(t.pow(period), BitMatrix::identity(32));
+        for p in [3, 5, 17, 257, 65537] {
+            assert_ne!(t.pow(period / p), BitMatrix::identity(32));
+        }
+    }
+}
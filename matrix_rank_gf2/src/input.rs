@@ -0,0 +1,101 @@
+//! Turning a byte stream into the values the tests consume.
+
+use std::io::{ErrorKind, Read};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+
+/// 32-bit values of `read`, until it runs out or fails.
+///
+/// A trailing partial value is dropped, and errors other than reaching the end are
+/// reported to stderr rather than panicking.
+pub fn values(mut read: impl Read, endian: Endian) -> impl Iterator<Item = u32> {
+    std::iter::from_fn(move || {
+        let mut bytes = [0u8; 4];
+        match read.read_exact(&mut bytes) {
+            Ok(()) => Some(match endian {
+                Endian::Little => u32::from_le_bytes(bytes),
+                Endian::Big => u32::from_be_bytes(bytes),
+            }),
+            Err(err) => {
+                if err.kind() != ErrorKind::UnexpectedEof {
+                    eprintln!("failed to read input: {}", err);
+                }
+                None
+            }
+        }
+    })
+}
+
+/// Which bits of each value are used, like the `r` and `s` parameters of TestU01: the `r`
+/// most significant bits are dropped and the next `s` bits are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extract {
+    pub r: u32,
+    pub s: u32,
+    /// Reverses the order of the 32 bits before extracting, so that `r = 0` keeps the `s`
+    /// least significant bits in reverse order and `r = 32 - s` the `s` most significant ones.
+    pub reverse: bool,
+}
+
+impl Default for Extract {
+    fn default() -> Self {
+        Self {
+            r: 0,
+            s: 32,
+            reverse: false,
+        }
+    }
+}
+
+impl Extract {
+    pub fn new(r: u32, s: u32, reverse: bool) -> Option<Self> {
+        (s >= 1 && r + s <= 32).then_some(Self { r, s, reverse })
+    }
+
+    /// The kept bits of `value`, as the low `s` bits.
+    pub fn apply(&self, value: u32) -> u32 {
+        let value = if self.reverse {
+            value.reverse_bits()
+        } else {
+            value
+        };
+        (value << self.r) >> (32 - self.s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values() {
+        let bytes = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        assert_eq!(
+            values(&bytes[..], Endian::Little).collect::<Vec<_>>(),
+            [0x0403_0201, 0x0807_0605]
+        );
+        assert_eq!(
+            values(&bytes[..], Endian::Big).collect::<Vec<_>>(),
+            [0x0102_0304, 0x0506_0708]
+        );
+    }
+
+    #[test]
+    fn test_extract() {
+        let value = 0xabcd_1234;
+        assert_eq!(Extract::default().apply(value), value);
+        // The top byte, the bottom byte, and the bottom byte reversed
+        assert_eq!(Extract::new(0, 8, false).unwrap().apply(value), 0xab);
+        assert_eq!(Extract::new(24, 8, false).unwrap().apply(value), 0x34);
+        assert_eq!(Extract::new(0, 8, true).unwrap().apply(value), 0x2c);
+        assert_eq!(Extract::new(4, 12, false).unwrap().apply(value), 0xbcd);
+
+        assert!(Extract::new(30, 3, false).is_none());
+        assert!(Extract::new(0, 0, false).is_none());
+    }
+}
@@ -1,40 +1,90 @@
+pub mod battery;
+pub mod bit_matrix;
+pub mod input;
+pub mod linear;
 pub mod plot;
+pub mod report;
+pub mod rng;
+pub mod runner;
+pub mod stats;
+pub mod transform;
+pub mod trials;
+
+use num_bigint::BigUint;
 
 pub const MATRIX_LEN: usize = 32;
 
-pub fn expected_rank_distribution() -> [f64; MATRIX_LEN + 1] {
-    let mut distribution = [0.0; MATRIX_LEN + 1];
-
-    const L: i32 = MATRIX_LEN as i32;
-    const K: i32 = u32::BITS as i32;
-
-    distribution[0] = 2.0_f64.powi(-L * K);
-    for x in 1..33 as i32 {
-        let a = 2.0_f64.powi(x * (L + K - x) - L * K);
-        let mut b = 1.0;
-        for i in 0..x {
-            b *= 1.0 - 2.0_f64.powi(i - L);
-            b *= 1.0 - 2.0_f64.powi(i - K);
-            b /= 1.0 - 2.0_f64.powi(i - x);
-        }
+/// Number of `l` x `k` matrices over GF(2) of each rank `0..=min(l, k)`, out of `2^(l k)`.
+///
+/// Uses `N(x + 1) = N(x) 2^x (2^(l - x) - 1) (2^(k - x) - 1) / (2^(x + 1) - 1)`, where each
+/// division is exact.
+pub fn rank_counts(l: usize, k: usize) -> Vec<BigUint> {
+    let one = BigUint::from(1u32);
+    let mersenne = |n: usize| (&one << n) - &one;
+
+    let mut counts = vec![one.clone()];
+    for x in 0..l.min(k) {
+        let next = (&counts[x] * mersenne(l - x) * mersenne(k - x)) << x;
+        counts.push(next / mersenne(x + 1));
+    }
+    counts
+}
+
+/// `n / 2^e` as `f64`, even when `n` alone is out of its range.
+fn scaled_to_f64(n: &BigUint, e: usize) -> f64 {
+    let shift = n.bits().saturating_sub(64);
+    let top = u64::try_from(n >> shift).unwrap() as f64;
 
-        distribution[x as usize] = a * b;
+    // Scaling in steps so that no intermediate power of two overflows or underflows
+    let mut e = shift as i64 - e as i64;
+    let mut x = top;
+    while e < -1000 {
+        x *= (-1000f64).exp2();
+        e += 1000;
     }
-    distribution
+    x * (e as f64).exp2()
 }
 
-// Matrix rank of bit matrix
+/// Probability of each rank `0..=min(L, K)` of a random `L` x `K` matrix over GF(2),
+/// rounded from the exact `rank_counts`. Probabilities below `f64::MIN_POSITIVE` are
+/// subnormal or zero, see `log2_rank_distribution` for them.
+pub fn rank_distribution(l: usize, k: usize) -> Vec<f64> {
+    rank_counts(l, k)
+        .iter()
+        .map(|n| scaled_to_f64(n, l * k))
+        .collect()
+}
+
+/// Base 2 logarithm of each probability of `rank_distribution`, which is finite even for
+/// ranks as unlikely as `2^(-l k)`.
+pub fn log2_rank_distribution(l: usize, k: usize) -> Vec<f64> {
+    rank_counts(l, k)
+        .iter()
+        .map(|n| {
+            let shift = n.bits().saturating_sub(64);
+            let top = u64::try_from(n >> shift).unwrap() as f64;
+            top.log2() + shift as f64 - (l * k) as f64
+        })
+        .collect()
+}
+
+pub fn expected_rank_distribution() -> [f64; MATRIX_LEN + 1] {
+    rank_distribution(MATRIX_LEN, u32::BITS as usize)
+        .try_into()
+        .unwrap()
+}
+
+/// Rank of the matrix whose rows are the 32 bits of each value of `matrix`, by Gaussian
+/// elimination in place.
+///
+/// Matrices of more than 32 columns need `bit_matrix::BitMatrix::rank`, which switches to
+/// the Method of Four Russians for large ones. With a single word per row this is faster
+/// than either, which also pay for converting to a `BitMatrix`.
 pub fn matrix_rank(matrix: &mut [u32]) -> usize {
     let n = matrix.len();
     let mut rank = 0;
     for bit in 0..u32::BITS {
-        let mut pivot = None;
-        for i in rank..n {
-            if (matrix[i] >> bit) & 1 == 1 {
-                pivot = Some(i);
-                break;
-            }
-        }
+        let pivot = (rank..n).find(|&i| (matrix[i] >> bit) & 1 == 1);
         if let Some(pivot) = pivot {
             matrix.swap(rank, pivot);
             for i in 0..n {
@@ -60,4 +110,91 @@ mod tests {
         let mut matrix = vec![0b0000, 0b0000, 0b0000];
         assert_eq!(matrix_rank(&mut matrix), 0);
     }
+
+    #[test]
+    fn test_rank_distribution_sums_to_one() {
+        for (l, k) in [
+            (1, 1),
+            (3, 5),
+            (32, 32),
+            (31, 33),
+            (64, 64),
+            (300, 300),
+            (1000, 1000),
+        ] {
+            let distribution = rank_distribution(l, k);
+            assert_eq!(distribution.len(), l.min(k) + 1);
+            let sum = distribution.iter().sum::<f64>();
+            assert!((sum - 1.0).abs() < 1e-12, "{}x{}: {}", l, k, sum);
+        }
+    }
+
+    #[test]
+    fn test_rank_counts_exact() {
+        for (l, k) in [(1, 1), (2, 2), (3, 5), (8, 6), (32, 32), (100, 37)] {
+            let sum = rank_counts(l, k).into_iter().sum::<BigUint>();
+            assert_eq!(sum, BigUint::from(1u32) << (l * k), "{}x{}", l, k);
+        }
+        // 3x3 matrices: 1 of rank 0, 49 of rank 1, 294 of rank 2 and 168 invertible
+        assert_eq!(
+            rank_counts(3, 3),
+            [1u32, 49, 294, 168].map(BigUint::from).to_vec()
+        );
+    }
+
+    #[test]
+    fn test_published_values() {
+        let assert_close = |a: f64, b: f64, eps: f64| {
+            assert!((a - b).abs() < eps, "{} != {}", a, b);
+        };
+
+        // DIEHARD's binary rank test on 31x31 matrices, given to 10 digits
+        let distribution = rank_distribution(31, 31);
+        assert_close(distribution[31], 0.288_788_095_2, 5e-11);
+        assert_close(distribution[30], 0.577_576_190_2, 5e-11);
+        assert_close(distribution[29], 0.128_350_264_4, 5e-11);
+        assert_close(distribution[..29].iter().sum(), 0.005_285_450_2, 5e-11);
+
+        // DIEHARD's binary rank test on 6x8 matrices, given to 6 digits
+        let distribution = rank_distribution(6, 8);
+        assert_close(distribution[6], 0.773_118, 5e-7);
+        assert_close(distribution[5], 0.217_439, 5e-7);
+        assert_close(distribution[..5].iter().sum(), 0.009_443, 5e-7);
+
+        // NIST SP 800-22's binary matrix rank test on 32x32 matrices
+        let distribution = rank_distribution(32, 32);
+        assert_close(distribution[32], 0.2888, 5e-5);
+        assert_close(distribution[31], 0.5776, 5e-5);
+        assert_close(distribution[..31].iter().sum(), 0.1336, 5e-5);
+
+        // Limits for large square matrices, e.g. prod(1 - 2^-i) for full rank
+        let distribution = rank_distribution(64, 64);
+        assert_close(distribution[64], 0.288_788_095_086_602_4, 1e-15);
+        assert_close(distribution[63], 0.577_576_190_173_204_8, 1e-15);
+        assert_close(distribution[62], 0.128_350_264_482_934_4, 1e-15);
+    }
+
+    #[test]
+    fn test_tails() {
+        // The zero matrix is the only one of rank 0
+        assert_eq!(log2_rank_distribution(32, 32)[0], -1024.0);
+        assert_eq!(log2_rank_distribution(1000, 1000)[0], -1e6);
+        assert_eq!(rank_distribution(32, 32)[0], (-1024f64).exp2());
+        assert_eq!(rank_distribution(1000, 1000)[0], 0.0);
+
+        // (2^l - 1) (2^k - 1) matrices of rank one, out of 2^(l k)
+        let log2 = log2_rank_distribution(40, 40)[1];
+        assert!((log2 - (2f64.powi(40) - 1.0).powi(2).log2() + 1600.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rank_distribution_small() {
+        // 1x1: rank 0 iff the only entry is 0
+        assert_eq!(rank_distribution(1, 1), vec![0.5, 0.5]);
+        // 2x2: 6 of the 16 matrices are invertible, and only the zero matrix has rank 0
+        let distribution = rank_distribution(2, 2);
+        assert!((distribution[0] - 1.0 / 16.0).abs() < 1e-15);
+        assert!((distribution[1] - 9.0 / 16.0).abs() < 1e-15);
+        assert!((distribution[2] - 6.0 / 16.0).abs() < 1e-15);
+    }
 }
@@ -0,0 +1,706 @@
+//! Analysis of generators whose state transition is linear over GF(2), such as xorshift.
+//!
+//! A transition is written as shift/xor operations on a state of several words. Its matrix
+//! is derived by stepping each unit vector, and its characteristic polynomial tells
+//! whether the generator has the full period `2^n - 1` and how to jump ahead.
+
+use std::fmt;
+
+use crate::bit_matrix::{BitMatrix, ones};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    /// `s[dst] ^= s[src] << shift`, or `>> -shift` for a negative `shift`. With `dst == src`
+    /// the shifted value is the old one, as in `x ^= x << a`.
+    Xor { dst: usize, src: usize, shift: i32 },
+    /// `s[i] = old s[(i + n) % words]`, e.g. `n = 1` for `x = y; y = z; z = w; w = x`.
+    RotateWords(usize),
+}
+
+/// Generators of [`crate::rng`] whose state transition is linear.
+pub const NAMES: [&str; 5] = [
+    "xorshift32",
+    "xorshift64",
+    "xorshift64star",
+    "xorshift128",
+    "xorshift128plus",
+];
+
+/// A state of `words` words of `bits` bits, updated by `ops` in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transition {
+    pub words: usize,
+    pub bits: u32,
+    pub ops: Vec<Op>,
+}
+
+impl Transition {
+    /// Transition of one of [`NAMES`].
+    pub fn by_name(name: &str) -> Option<Self> {
+        use Op::*;
+
+        let xorshift = |bits, a, b, c| Transition {
+            words: 1,
+            bits,
+            ops: vec![
+                Xor {
+                    dst: 0,
+                    src: 0,
+                    shift: a,
+                },
+                Xor {
+                    dst: 0,
+                    src: 0,
+                    shift: b,
+                },
+                Xor {
+                    dst: 0,
+                    src: 0,
+                    shift: c,
+                },
+            ],
+        };
+
+        Some(match name {
+            "xorshift32" => xorshift(32, 13, -17, 5),
+            "xorshift64" => xorshift(64, 13, -7, 17),
+            // Also the state transition of xorshift64*, whose multiplication is on the output
+            "xorshift64star" => xorshift(64, -12, 25, -27),
+            // t = x ^ (x << 11); x = y; y = z; z = w; w ^= (w >> 19) ^ t ^ (t >> 8)
+            "xorshift128" => Transition {
+                words: 4,
+                bits: 32,
+                ops: vec![
+                    Xor {
+                        dst: 0,
+                        src: 0,
+                        shift: 11,
+                    },
+                    Xor {
+                        dst: 0,
+                        src: 0,
+                        shift: -8,
+                    },
+                    Xor {
+                        dst: 0,
+                        src: 3,
+                        shift: 0,
+                    },
+                    Xor {
+                        dst: 0,
+                        src: 3,
+                        shift: -19,
+                    },
+                    RotateWords(1),
+                ],
+            },
+            // s1 = s[0], s0 = s[1]; s1 ^= s1 << 23; s = [s0, s1 ^ s0 ^ (s1 >> 18) ^ (s0 >> 5)]
+            "xorshift128plus" => Transition {
+                words: 2,
+                bits: 64,
+                ops: vec![
+                    Xor {
+                        dst: 0,
+                        src: 0,
+                        shift: 23,
+                    },
+                    Xor {
+                        dst: 0,
+                        src: 0,
+                        shift: -18,
+                    },
+                    Xor {
+                        dst: 0,
+                        src: 1,
+                        shift: 0,
+                    },
+                    Xor {
+                        dst: 0,
+                        src: 1,
+                        shift: -5,
+                    },
+                    RotateWords(1),
+                ],
+            },
+            _ => return None,
+        })
+    }
+
+    /// Number of bits of the state.
+    pub fn len(&self) -> usize {
+        self.words * self.bits as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn mask(&self) -> u64 {
+        u64::MAX >> (64 - self.bits)
+    }
+
+    /// Advances `state`, made of `words` words, by one step.
+    pub fn step(&self, state: &mut [u64]) {
+        assert_eq!(state.len(), self.words);
+        for op in &self.ops {
+            match *op {
+                Op::Xor { dst, src, shift } => {
+                    let shifted = if shift >= 0 {
+                        state[src].checked_shl(shift as u32).unwrap_or(0)
+                    } else {
+                        state[src].checked_shr(shift.unsigned_abs()).unwrap_or(0)
+                    };
+                    state[dst] = (state[dst] ^ shifted) & self.mask();
+                }
+                Op::RotateWords(n) => state.rotate_left(n % self.words),
+            }
+        }
+    }
+
+    /// Packs a state so that bit `j` of word `i` is bit `i * bits + j`.
+    pub fn pack(&self, state: &[u64]) -> Vec<u64> {
+        let mut packed = vec![0; self.len().div_ceil(64)];
+        for (i, word) in state.iter().enumerate() {
+            for j in ones(&[*word]) {
+                let bit = i * self.bits as usize + j;
+                packed[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        packed
+    }
+
+    pub fn unpack(&self, packed: &[u64]) -> Vec<u64> {
+        let mut state = vec![0; self.words];
+        for bit in ones(packed) {
+            state[bit / self.bits as usize] |= 1 << (bit % self.bits as usize);
+        }
+        state
+    }
+
+    /// Matrix `A` of the transition, such that the next packed state is `A * state`.
+    ///
+    /// Column `c` is the step of the unit vector `e_c`, which is valid since `step` is
+    /// linear.
+    pub fn matrix(&self) -> BitMatrix {
+        let n = self.len();
+        let mut columns = Vec::with_capacity(n);
+        for c in 0..n {
+            let mut unit = vec![0; n.div_ceil(64)];
+            unit[c / 64] = 1 << (c % 64);
+            let mut state = self.unpack(&unit);
+            self.step(&mut state);
+            columns.push(self.pack(&state));
+        }
+        BitMatrix::from_rows(n, &columns).transpose()
+    }
+
+    /// Advances `state` by `k` steps, where `jump` is `x^k mod p` for the characteristic
+    /// polynomial `p`, in `O(n)` steps whatever `k` is.
+    pub fn jump(&self, jump: &Poly, state: &mut [u64]) {
+        // By Cayley–Hamilton A^k = sum of jump_i A^i, evaluated by stepping
+        let mut result = vec![0; self.words];
+        for i in 0..=jump.degree().unwrap_or(0) {
+            if jump.coefficient(i) {
+                for (r, s) in result.iter_mut().zip(state.iter()) {
+                    *r ^= s;
+                }
+            }
+            self.step(state);
+        }
+        state.copy_from_slice(&result);
+    }
+}
+
+/// Polynomial over GF(2), whose bit `i` is the coefficient of `x^i`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Poly(Vec<u64>);
+
+impl Poly {
+    pub fn zero() -> Self {
+        Poly(Vec::new())
+    }
+
+    pub fn one() -> Self {
+        Poly::monomial(0)
+    }
+
+    /// `x^i`
+    pub fn monomial(i: usize) -> Self {
+        let mut poly = Poly(vec![0; i / 64 + 1]);
+        poly.0[i / 64] = 1 << (i % 64);
+        poly
+    }
+
+    /// Coefficients packed into words, lowest degree first, like the jump constants of
+    /// xoroshiro and friends.
+    pub fn words(&self) -> &[u64] {
+        &self.0
+    }
+
+    pub fn from_words(words: Vec<u64>) -> Self {
+        let mut poly = Poly(words);
+        poly.trim();
+        poly
+    }
+
+    fn trim(&mut self) {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+    }
+
+    /// `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        let last = self.0.iter().rposition(|&w| w != 0)?;
+        Some(last * 64 + 63 - self.0[last].leading_zeros() as usize)
+    }
+
+    pub fn coefficient(&self, i: usize) -> bool {
+        self.0.get(i / 64).is_some_and(|w| (w >> (i % 64)) & 1 == 1)
+    }
+
+    /// Number of nonzero coefficients, which should be about half of the degree for a
+    /// generator of good quality.
+    pub fn weight(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    fn add_assign(&mut self, other: &Poly) {
+        if self.0.len() < other.0.len() {
+            self.0.resize(other.0.len(), 0);
+        }
+        for (a, b) in self.0.iter_mut().zip(&other.0) {
+            *a ^= b;
+        }
+        self.trim();
+    }
+
+    /// `self * x`
+    fn shift(&self) -> Poly {
+        let mut words = Vec::with_capacity(self.0.len() + 1);
+        let mut carry = 0;
+        for &w in &self.0 {
+            words.push((w << 1) | carry);
+            carry = w >> 63;
+        }
+        words.push(carry);
+        Poly::from_words(words)
+    }
+
+    /// `self * other mod modulus`
+    pub fn mul_mod(&self, other: &Poly, modulus: &Poly) -> Poly {
+        let n = modulus.degree().expect("modulus must not be zero");
+        let mut result = Poly::zero();
+        // Horner's method over the coefficients of `other`, reducing after each shift
+        for i in (0..=other.degree().unwrap_or(0)).rev() {
+            result = result.shift();
+            if result.coefficient(n) {
+                result.add_assign(modulus);
+            }
+            if other.coefficient(i) {
+                result.add_assign(self);
+            }
+        }
+        result.rem(modulus)
+    }
+
+    pub fn rem(&self, modulus: &Poly) -> Poly {
+        let n = modulus.degree().expect("modulus must not be zero");
+        let mut result = self.clone();
+        while let Some(d) = result.degree().filter(|&d| d >= n) {
+            let mut shifted = Poly::zero();
+            for i in ones(&modulus.0) {
+                shifted.add_assign(&Poly::monomial(i + d - n));
+            }
+            result.add_assign(&shifted);
+        }
+        result
+    }
+
+    /// `x^e mod self`
+    pub fn pow_x(&self, e: u128) -> Poly {
+        let mut result = Poly::one().rem(self);
+        for i in (0..128 - e.leading_zeros()).rev() {
+            result = result.mul_mod(&result, self);
+            if (e >> i) & 1 == 1 {
+                result = result.shift().rem(self);
+            }
+        }
+        result
+    }
+
+    /// Whether `self`, of degree `n <= 128`, is primitive, i.e. `x` has the multiplicative
+    /// order `2^n - 1` modulo `self`.
+    ///
+    /// Only a field has units of that order, so this also checks irreducibility.
+    pub fn is_primitive(&self) -> bool {
+        let n = self.degree().expect("zero polynomial");
+        assert!(n <= 128, "degree {} is too large", n);
+        if n == 0 {
+            return false;
+        }
+        let order = u128::MAX >> (128 - n);
+        self.pow_x(order) == Poly::one()
+            && prime_factors(order)
+                .iter()
+                .all(|&q| self.pow_x(order / q) != Poly::one())
+    }
+}
+
+impl fmt::Display for Poly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms = ones(&self.0)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .map(|i| match i {
+                0 => "1".to_string(),
+                1 => "x".to_string(),
+                _ => format!("x^{}", i),
+            })
+            .collect::<Vec<_>>();
+        if terms.is_empty() {
+            write!(f, "0")
+        } else {
+            write!(f, "{}", terms.join(" + "))
+        }
+    }
+}
+
+/// Characteristic polynomial of a square matrix.
+///
+/// The matrix is brought to upper Hessenberg form by similarity transforms, from which
+/// the polynomial follows by the usual recurrence on its leading principal minors.
+pub fn characteristic_polynomial(matrix: &BitMatrix) -> Poly {
+    let n = matrix.rows();
+    assert_eq!(n, matrix.cols());
+
+    let mut h = matrix.clone();
+    let swap_cols = |h: &mut BitMatrix, a: usize, b: usize| {
+        for r in 0..n {
+            let (x, y) = (h.get(r, a), h.get(r, b));
+            h.set(r, a, y);
+            h.set(r, b, x);
+        }
+    };
+
+    for j in 0..n.saturating_sub(2) {
+        let Some(pivot) = (j + 1..n).find(|&r| h.get(r, j)) else {
+            continue;
+        };
+        h.swap_rows(j + 1, pivot);
+        swap_cols(&mut h, j + 1, pivot);
+
+        for r in j + 2..n {
+            if h.get(r, j) {
+                // Row r += row j + 1, then column j + 1 += column r to keep it similar
+                for c in 0..n {
+                    if h.get(j + 1, c) {
+                        h.set(r, c, !h.get(r, c));
+                    }
+                }
+                for i in 0..n {
+                    if h.get(i, r) {
+                        h.set(i, j + 1, !h.get(i, j + 1));
+                    }
+                }
+            }
+        }
+    }
+
+    // p[k] is the characteristic polynomial of the leading k x k block
+    let mut p = vec![Poly::one()];
+    for k in 1..=n {
+        let mut next = p[k - 1].shift();
+        if h.get(k - 1, k - 1) {
+            next.add_assign(&p[k - 1]);
+        }
+        for i in (1..k).rev() {
+            // Product of the subdiagonal from row i to k - 1
+            if !h.get(i, i - 1) {
+                break;
+            }
+            if h.get(i - 1, k - 1) {
+                next.add_assign(&p[i - 1]);
+            }
+        }
+        p.push(next);
+    }
+    p.pop().unwrap()
+}
+
+fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    if let (Ok(a), Ok(b)) = (u64::try_from(a), u64::try_from(b)) {
+        return (a as u128 * b as u128) % m;
+    }
+    // Double and add, as the product may not fit in 128 bits
+    let (mut a, mut b, mut result) = (a % m, b, 0u128);
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, a, m);
+        }
+        a = add_mod(a, a, m);
+        b >>= 1;
+    }
+    result
+}
+
+fn add_mod(a: u128, b: u128, m: u128) -> u128 {
+    if a >= m - b { a - (m - b) } else { a + b }
+}
+
+fn pow_mod(mut base: u128, mut e: u128, m: u128) -> u128 {
+    let mut result = 1 % m;
+    base %= m;
+    while e > 0 {
+        if e & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        e >>= 1;
+    }
+    result
+}
+
+/// Miller–Rabin test with the first 12 prime bases, which is exact below 3.3 * 10^24 and
+/// has never been known to fail above.
+fn is_prime(n: u128) -> bool {
+    const BASES: [u128; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    if let Some(&p) = BASES.iter().find(|&&p| n.is_multiple_of(p)) {
+        return n == p;
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    BASES.iter().all(|&a| {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        (1..s).any(|_| {
+            x = mul_mod(x, x, n);
+            x == n - 1
+        })
+    })
+}
+
+/// A nontrivial factor of the odd composite `n`, by Pollard's rho with Brent's cycle
+/// detection.
+fn pollard_rho(n: u128) -> u128 {
+    let gcd = |mut a: u128, mut b: u128| {
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    };
+
+    for c in 1.. {
+        let f = |x: u128| add_mod(mul_mod(x, x, n), c, n);
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        let mut power = 1;
+        let mut lam = 0;
+        while d == 1 {
+            if power == lam {
+                x = y;
+                power *= 2;
+                lam = 0;
+            }
+            y = f(y);
+            lam += 1;
+            d = gcd(x.abs_diff(y), n);
+        }
+        if d != n {
+            return d;
+        }
+    }
+    unreachable!()
+}
+
+/// Distinct prime factors of `n`, in increasing order.
+pub fn prime_factors(mut n: u128) -> Vec<u128> {
+    let mut factors = Vec::new();
+    let mut p = 2;
+    while p < 1 << 12 && p * p <= n {
+        if n.is_multiple_of(p) {
+            factors.push(p);
+            while n.is_multiple_of(p) {
+                n /= p;
+            }
+        }
+        p += 1;
+    }
+
+    let mut stack = vec![n];
+    while let Some(m) = stack.pop() {
+        if m == 1 {
+            continue;
+        }
+        if is_prime(m) {
+            factors.push(m);
+            continue;
+        }
+        let d = pollard_rho(m);
+        stack.push(d);
+        stack.push(m / d);
+    }
+
+    factors.sort_unstable();
+    factors.dedup();
+    factors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{Generator, Xorshift32, Xorshift64, Xorshift128, Xorshift128Plus};
+
+    #[test]
+    fn test_step_matches_rng() {
+        let xorshift32 = Transition::by_name("xorshift32").unwrap();
+        let mut reference = Xorshift32::new(2463534242);
+        let mut state = [2463534242];
+        for _ in 0..10 {
+            xorshift32.step(&mut state);
+            assert_eq!(state[0], reference.next());
+        }
+
+        let xorshift64 = Transition::by_name("xorshift64").unwrap();
+        let mut reference = Xorshift64::new(88172645463325252);
+        let mut state = [88172645463325252];
+        xorshift64.step(&mut state);
+        assert_eq!(state[0], reference.next());
+
+        let xorshift128 = Transition::by_name("xorshift128").unwrap();
+        let seed = [123456789, 362436069, 521288629, 88675123];
+        let mut reference = Xorshift128::new(seed);
+        let mut state = seed.map(u64::from);
+        for _ in 0..10 {
+            xorshift128.step(&mut state);
+            assert_eq!(state[3], reference.next());
+        }
+
+        // The output of xorshift128+ is the sum of the two words of the previous state
+        let plus = Transition::by_name("xorshift128plus").unwrap();
+        let mut reference = Xorshift128Plus::new([1, 2]);
+        let mut state = [1u64, 2];
+        for _ in 0..10 {
+            let output = state[0].wrapping_add(state[1]);
+            plus.step(&mut state);
+            assert_eq!(output, reference.next());
+        }
+    }
+
+    #[test]
+    fn test_matrix() {
+        let transition = Transition::by_name("xorshift128").unwrap();
+        let matrix = transition.matrix();
+        let mut state = [1, 22, 333, 4444];
+        let next = matrix.mul_vec(&transition.pack(&state));
+        transition.step(&mut state);
+        assert_eq!(transition.unpack(&next), state);
+    }
+
+    #[test]
+    fn test_poly() {
+        // (x + 1)^2 = x^2 + 1
+        let p = Poly::from_words(vec![0b11]);
+        assert_eq!(p.mul_mod(&p, &Poly::monomial(5)).words(), [0b101]);
+        assert_eq!(Poly::from_words(vec![0b1011]).to_string(), "x^3 + x + 1");
+        assert_eq!(Poly::monomial(70).degree(), Some(70));
+        assert_eq!(Poly::zero().degree(), None);
+
+        // x^3 + x + 1 is primitive, x^4 + x^3 + x^2 + x + 1 irreducible but of order 5 and
+        // x^2 + 1 reducible
+        assert!(Poly::from_words(vec![0b1011]).is_primitive());
+        assert!(!Poly::from_words(vec![0b11111]).is_primitive());
+        assert!(!Poly::from_words(vec![0b101]).is_primitive());
+        assert_eq!(Poly::from_words(vec![0b11111]).pow_x(5), Poly::one());
+    }
+
+    #[test]
+    fn test_prime_factors() {
+        assert_eq!(prime_factors(u32::MAX as u128), [3, 5, 17, 257, 65537]);
+        assert_eq!(
+            prime_factors(u64::MAX as u128),
+            [3, 5, 17, 257, 641, 65537, 6700417]
+        );
+        assert_eq!(
+            prime_factors(u128::MAX),
+            [3, 5, 17, 257, 641, 65537, 274177, 6700417, 67280421310721]
+        );
+        assert_eq!(prime_factors((1 << 61) - 1), [(1 << 61) - 1]);
+    }
+
+    #[test]
+    fn test_characteristic_polynomial() {
+        // Cayley–Hamilton: p(A) = 0
+        for name in ["xorshift32", "xorshift128"] {
+            let a = Transition::by_name(name).unwrap().matrix();
+            let p = characteristic_polynomial(&a);
+            assert_eq!(p.degree(), Some(a.rows()));
+
+            let mut sum = BitMatrix::zeros(a.rows(), a.cols());
+            let mut power = BitMatrix::identity(a.rows());
+            for i in 0..=a.rows() {
+                if p.coefficient(i) {
+                    for r in 0..a.rows() {
+                        for c in ones(power.row(r)) {
+                            sum.set(r, c, !sum.get(r, c));
+                        }
+                    }
+                }
+                power = &power * &a;
+            }
+            assert_eq!(sum, BitMatrix::zeros(a.rows(), a.cols()), "{}", name);
+        }
+
+        // x ^= x << 1 is unipotent
+        let transition = Transition {
+            words: 1,
+            bits: 8,
+            ops: vec![Op::Xor {
+                dst: 0,
+                src: 0,
+                shift: 1,
+            }],
+        };
+        let p = characteristic_polynomial(&transition.matrix());
+        assert_eq!(p, Poly::from_words(vec![0x101]));
+        assert!(!p.is_primitive());
+    }
+
+    #[test]
+    fn test_full_period() {
+        for name in NAMES {
+            let transition = Transition::by_name(name).unwrap();
+            let p = characteristic_polynomial(&transition.matrix());
+            assert!(p.is_primitive(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_jump() {
+        let transition = Transition::by_name("xorshift128").unwrap();
+        let p = characteristic_polynomial(&transition.matrix());
+
+        let mut expected = [1, 2, 3, 4];
+        for _ in 0..1000 {
+            transition.step(&mut expected);
+        }
+        let mut state = [1, 2, 3, 4];
+        transition.jump(&p.pow_x(1000), &mut state);
+        assert_eq!(state, expected);
+
+        // Jumping 2^64 steps agrees with the matrix power
+        let k = 1 << 64;
+        let mut state = [1, 2, 3, 4];
+        transition.jump(&p.pow_x(k), &mut state);
+        let packed = transition
+            .matrix()
+            .pow(k)
+            .mul_vec(&transition.pack(&[1, 2, 3, 4]));
+        assert_eq!(state.to_vec(), transition.unpack(&packed));
+    }
+}
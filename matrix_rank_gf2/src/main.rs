@@ -1,41 +1,500 @@
-use std::io::Read;
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use matrix_rank_gf2::{MATRIX_LEN, matrix_rank, plot::plot};
+use clap::{
+    CommandFactory, Parser, Subcommand, ValueEnum, builder::PossibleValuesParser, error::ErrorKind,
+};
+use matrix_rank_gf2::{
+    battery,
+    input::{self, Endian, Extract},
+    linear::{self, Transition, characteristic_polynomial},
+    plot::{PlotOptions, plot},
+    rank_distribution,
+    report::{self, Run},
+    rng,
+    runner::{self, Options, RankResult},
+    stats::MIN_EXPECTED,
+    transform::{self, Combine},
+    trials::Shape,
+};
 
-fn main() {
-    // let expected = expected_rank_distribution();
-
-    let mut counts = [0usize; MATRIX_LEN + 1];
+#[derive(Parser)]
+#[command(about = "MatrixRank test over GF(2) of random bytes read from stdin or generated")]
+struct Args {
+    /// Number of rows (L) of each matrix
+    #[arg(long, default_value_t = 32)]
+    rows: usize,
+    /// Number of columns (K) of each matrix
+    #[arg(long, default_value_t = 32)]
+    cols: usize,
+    /// Number of matrices to test
+    #[arg(long, value_parser = parse_positive, default_value = "1000000")]
+    trials: usize,
+    /// Number of worker threads ranking matrices, which does not change the results
+    #[arg(long, default_value_t = default_threads())]
+    threads: usize,
+    /// Number of most significant bits of each output to drop (TestU01's r)
+    #[arg(short, long, default_value_t = 0)]
+    r: u32,
+    /// Number of bits of each output to use after the dropped ones (TestU01's s), e.g.
+    /// `-r 24 -s 8` for the low byte
+    #[arg(short, long, default_value_t = 32)]
+    s: u32,
+    /// Reverse the bits of each output before extracting them
+    #[arg(long)]
+    reverse_bits: bool,
+    /// Test every K-th output only
+    #[arg(long, value_name = "K", value_parser = parse_positive, default_value = "1")]
+    decimate: usize,
+    /// Fill each matrix with rows D rows apart, regrouping D consecutive matrices at once
+    #[arg(long, value_name = "D", value_parser = parse_positive, default_value = "1")]
+    lag: usize,
+    /// Number of streams of each generator to combine, seeded with --seed, --seed + 1 and
+    /// so on
+    #[arg(long, value_name = "N", value_parser = parse_positive, default_value = "1")]
+    streams: usize,
+    /// How to combine the streams
+    #[arg(long, value_enum, default_value_t = Combine::Interleave)]
+    combine: Combine,
+    /// Byte order of the input
+    #[arg(long, global = true, value_enum, default_value_t = Endian::Little)]
+    endian: Endian,
+    /// File to read instead of stdin
+    #[arg(long, global = true, conflicts_with = "generator")]
+    input: Option<PathBuf>,
+    /// Built-in generators to test instead of reading stdin, one after another
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        value_parser = PossibleValuesParser::new(rng::NAMES)
+    )]
+    generator: Vec<String>,
+    /// Seed of the built-in generator
+    #[arg(long, global = true, default_value_t = 0)]
+    seed: u64,
+    /// Where to plot the distributions, as SVG if the extension is .svg and PNG otherwise
+    #[arg(long, default_value = "1.png")]
+    plot: PathBuf,
+    /// Size of the plot in pixels
+    #[arg(long, value_parser = parse_size, default_value = "640x480")]
+    plot_size: (u32, u32),
+    /// Plot probabilities on a log scale to show the tails of the distribution
+    #[arg(long)]
+    log_scale: bool,
+    /// Do not plot
+    #[arg(long, conflicts_with_all = ["plot", "plot_size", "log_scale"])]
+    no_plot: bool,
+    /// Print the running chi-square to stderr every this many seconds
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    progress: Option<Duration>,
+    /// Stop as soon as the running p-value is below this, which is checked many times and
+    /// should thus be far below the level the result is judged at
+    #[arg(long, value_name = "P_VALUE")]
+    stop_below: Option<f64>,
+    /// Write the results as JSON to this file
+    #[arg(long)]
+    json: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
 
-    let trials = 1_000_000;
+#[derive(Subcommand)]
+enum Command {
+    /// Run a battery of several tests and print a TestU01-like summary
+    Battery,
+    /// Run the rank test with each seed of each generator, or of every deterministic one
+    /// without --generator, and write the results with a plot per generator and a report
+    Compare {
+        /// Seeds to run each generator with
+        #[arg(long, value_delimiter = ',', default_value = "0")]
+        seeds: Vec<u64>,
+        /// Directory to write results.json, results.csv, timings.csv, report.md and the
+        /// plots to
+        #[arg(long, default_value = "report")]
+        out: PathBuf,
+        /// Image format of the plots
+        #[arg(long, value_enum, default_value_t = ImageFormat::Png)]
+        format: ImageFormat,
+    },
+    /// Print the characteristic polynomial, period and jump-ahead polynomial of a generator
+    /// with a linear state transition
+    Analyze {
+        #[arg(value_name = "GENERATOR", value_parser = PossibleValuesParser::new(linear::NAMES))]
+        name: String,
+        /// Number of steps to jump ahead, as a number or a power of two such as 2^64
+        #[arg(long, value_parser = parse_steps, default_value = "2^64")]
+        jump: u128,
+    },
+}
 
-    let mut stdin = std::io::stdin();
-    for _ in 0..trials {
-        let mut matrix = [0u8; MATRIX_LEN * 4];
-        stdin.read_exact(&mut matrix).unwrap();
+#[derive(Clone, Copy, ValueEnum)]
+enum ImageFormat {
+    Png,
+    Svg,
+}
 
-        let mut bit_matrix = [0u32; MATRIX_LEN];
-        for i in 0..MATRIX_LEN {
-            bit_matrix[i] = u32::from_le_bytes([
-                matrix[i * 4],
-                matrix[i * 4 + 1],
-                matrix[i * 4 + 2],
-                matrix[i * 4 + 3],
-            ]);
+impl ImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
         }
-        let rank = matrix_rank(&mut bit_matrix);
-        counts[rank] += 1;
     }
+}
+
+fn parse_steps(s: &str) -> Result<u128, String> {
+    match s.split_once('^') {
+        Some(("2", e)) => match e.parse::<u32>() {
+            Ok(e) if e < 128 => Ok(1 << e),
+            _ => Err(format!("{}: exponent must be below 128", e)),
+        },
+        Some(_) => Err("only powers of 2 are supported".to_string()),
+        None => s.parse().map_err(|err| format!("{}: {}", s, err)),
+    }
+}
+
+/// Prints what the state transition of `name` implies for its period and streams.
+fn analyze(name: &str, jump: u128) {
+    let transition = Transition::by_name(name).unwrap();
+    let n = transition.len();
+    let p = characteristic_polynomial(&transition.matrix());
+    println!("{}: {}-bit state", name, n);
+    println!("characteristic polynomial ({} terms): {}", p.weight(), p);
+    if p.is_primitive() {
+        println!("primitive: full period 2^{} - 1", n);
+    } else {
+        println!(
+            "not primitive: some seeds have a period shorter than 2^{} - 1",
+            n
+        );
+    }
+
+    // Streams of parallel workers start `jump` steps apart, each jumping from the last
+    let words = p.pow_x(jump).words().to_vec();
+    let words = (0..n.div_ceil(64))
+        .map(|i| format!("{:#018x}", words.get(i).copied().unwrap_or(0)))
+        .collect::<Vec<_>>();
+    println!(
+        "jump {} steps: const JUMP: [u64; {}] = [{}];",
+        jump,
+        words.len(),
+        words.join(", ")
+    );
+}
+
+fn parse_size(s: &str) -> Result<(u32, u32), String> {
+    let (w, h) = s.split_once('x').ok_or("expected WIDTHxHEIGHT")?;
+    let parse = |n: &str| n.parse::<u32>().map_err(|err| format!("{}: {}", n, err));
+    Ok((parse(w)?, parse(h)?))
+}
+
+fn parse_seconds(s: &str) -> Result<Duration, String> {
+    s.parse::<f64>()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| format!("{}: expected a non-negative number of seconds", s))
+}
+
+fn parse_positive(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(n) => Ok(n),
+        Err(err) => Err(format!("{}: {}", s, err)),
+    }
+}
+
+fn default_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+type Values = Box<dyn Iterator<Item = u32>>;
+
+/// Values of `--streams` streams of the generator `name` from `seed` on, combined and
+/// decimated.
+fn generator_values(args: &Args, name: &str, seed: u64) -> io::Result<Values> {
+    let streams = (0..args.streams as u64)
+        .map(|i| {
+            Ok(input::values(
+                rng::open(name, seed.wrapping_add(i))?,
+                args.endian,
+            ))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    let values = transform::combine(streams, args.combine);
+    Ok(Box::new(transform::decimate(values, args.decimate)))
+}
+
+/// Values to test with their names: one per generator, or of a file or stdin.
+fn inputs(args: &Args) -> Vec<(String, Values)> {
+    if !args.generator.is_empty() {
+        return args
+            .generator
+            .iter()
+            .map(|name| match generator_values(args, name, args.seed) {
+                Ok(values) => (name.clone(), values),
+                Err(err) => {
+                    eprintln!("failed to open {}: {}", name, err);
+                    std::process::exit(2);
+                }
+            })
+            .collect();
+    }
+    if args.streams > 1 {
+        eprintln!("--streams needs --generator");
+        std::process::exit(2);
+    }
+
+    let (name, read): (String, Box<dyn Read>) = match &args.input {
+        Some(path) => match File::open(path) {
+            Ok(file) => (path.display().to_string(), Box::new(BufReader::new(file))),
+            Err(err) => {
+                eprintln!("failed to open {}: {}", path.display(), err);
+                std::process::exit(2);
+            }
+        },
+        None => ("stdin".to_string(), Box::new(std::io::stdin().lock())),
+    };
+    let values = input::values(read, args.endian);
+    vec![(name, Box::new(transform::decimate(values, args.decimate)))]
+}
+
+/// Runs the rank test on `values`, reporting progress and how it ended to stderr.
+fn run_rank(
+    args: &Args,
+    name: &str,
+    extract: Extract,
+    values: impl Iterator<Item = u32>,
+) -> RankResult {
+    let options = Options {
+        shape: Shape {
+            rows: args.rows,
+            cols: args.cols,
+            bits: extract.s,
+        },
+        trials: args.trials,
+        threads: args.threads,
+        progress: args.progress,
+        stop_below: args.stop_below,
+    };
+
+    let per_row = args.cols.div_ceil(extract.s as usize);
+    let values = values.map(|v| extract.apply(v));
+    let mut values = transform::lag(values, args.lag, args.rows, per_row);
+    let result = runner::run(name, &options, &mut values, |progress| {
+        eprintln!(
+            "{}: {} of {} matrices in {:.1}s, chi-square = {:.4} (dof = {}), p-value = {:.4e}",
+            name,
+            progress.matrices,
+            args.trials,
+            progress.elapsed.as_secs_f64(),
+            progress.chi_square.statistic,
+            progress.chi_square.dof,
+            progress.chi_square.p_value
+        );
+    });
+    if result.matrices == 0 {
+        eprintln!("input ended before the first matrix");
+        std::process::exit(2);
+    }
+    if result.stopped_early {
+        eprintln!(
+            "stopped after {} of {} matrices as the p-value fell below {:e}",
+            result.matrices,
+            args.trials,
+            args.stop_below.unwrap()
+        );
+    } else if result.matrices < args.trials {
+        eprintln!(
+            "input ended after {} of {} matrices, testing them only",
+            result.matrices, args.trials
+        );
+    }
+    result
+}
+
+/// Runs the rank test on `values` and prints its results.
+fn rank_test(
+    args: &Args,
+    name: &str,
+    extract: Extract,
+    values: impl Iterator<Item = u32>,
+) -> RankResult {
+    let max_rank = args.rows.min(args.cols);
+    let result = run_rank(args, name, extract, values);
 
-    /*
-    let dist = counts.map(|c| c as f64 / trials as f64);
+    let dist = result.distribution();
+    let expected = &result.expected;
     println!("Rank Distribution (Observed vs Expected):");
     println!("Rank\tObserved\tExpected");
-    for i in 0..=32 {
+    for i in 0..=max_rank {
         println!("{:>2}\t{:.6}\t{:.6}", i, dist[i], expected[i]);
     }
-    */
 
-    let dist = counts.map(|c| c as f32 / trials as f32);
-    plot(&dist).unwrap();
+    println!();
+    println!("Classes (ranks merged until expected count >= {MIN_EXPECTED}):");
+    for class in &result.classes {
+        let observed = result.counts[class.clone()].iter().sum::<usize>();
+        let expected = expected[class.clone()].iter().sum::<f64>() * result.matrices as f64;
+        if class.len() == 1 {
+            println!("{:>8}\t{:>8}\t{:.1}", class.start, observed, expected);
+        } else {
+            println!(
+                "{:>2}..={:>2}\t{:>8}\t{:.1}",
+                class.start,
+                class.end - 1,
+                observed,
+                expected
+            );
+        }
+    }
+    println!(
+        "chi-square = {:.4} (dof = {}), p-value = {:.4e}: {}",
+        result.chi_square, result.dof, result.p_value, result.verdict
+    );
+
+    result
+}
+
+/// Runs the rank test with each of `seeds` for each generator and writes the results
+/// with a plot per generator and a markdown report to `out`.
+fn compare(
+    args: &Args,
+    extract: Extract,
+    seeds: &[u64],
+    out: &Path,
+    format: ImageFormat,
+) -> io::Result<()> {
+    let generators = if args.generator.is_empty() {
+        // Only those which a rerun reproduces
+        rng::NAMES
+            .iter()
+            .filter(|&&name| name != "urandom")
+            .map(|name| name.to_string())
+            .collect()
+    } else {
+        args.generator.clone()
+    };
+    let expected = rank_distribution(args.rows, args.cols);
+    std::fs::create_dir_all(out)?;
+
+    let mut runs = Vec::new();
+    let mut plots = Vec::new();
+    for name in &generators {
+        let mut observed = Vec::new();
+        for &seed in seeds {
+            let values = generator_values(args, name, seed)?;
+            let result = run_rank(args, name, extract, values);
+            println!(
+                "{} (seed {}): chi-square = {:.4} (dof = {}), p-value = {:.4e}: {}",
+                name, seed, result.chi_square, result.dof, result.p_value, result.verdict
+            );
+            observed.push((format!("seed {}", seed), result.distribution()));
+            runs.push(Run { seed, result });
+        }
+
+        let image = format!("{}.{}", name, format.extension());
+        let options = PlotOptions {
+            path: out.join(&image),
+            size: args.plot_size,
+            log_scale: args.log_scale,
+        };
+        plot(&options, &expected, &observed).map_err(|err| io::Error::other(err.to_string()))?;
+        plots.push((name.clone(), image));
+    }
+
+    serde_json::to_writer_pretty(File::create(out.join("results.json"))?, &runs)?;
+    report::write_csv(File::create(out.join("results.csv"))?, &runs)?;
+    report::write_timings(File::create(out.join("timings.csv"))?, &runs)?;
+    report::write_markdown(File::create(out.join("report.md"))?, &runs, &plots)
+}
+
+fn main() {
+    let args = Args::parse();
+    let Some(extract) = Extract::new(args.r, args.s, args.reverse_bits) else {
+        Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "-s must be at least 1 and -r + -s at most 32",
+            )
+            .exit();
+    };
+
+    match &args.command {
+        Some(Command::Analyze { name, jump }) => {
+            analyze(name, *jump);
+            return;
+        }
+        Some(Command::Compare { seeds, out, format }) => {
+            if let Err(err) = compare(&args, extract, seeds, out, *format) {
+                eprintln!("failed to write the report to {}: {}", out.display(), err);
+                std::process::exit(2);
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let inputs = inputs(&args);
+    let count = inputs.len();
+    let mut observed = Vec::new();
+    let mut results = Vec::new();
+    let mut battery_results = Vec::new();
+
+    for (name, mut values) in inputs {
+        if count > 1 {
+            println!("==================== {} ====================", name);
+        }
+
+        if let Some(Command::Battery) = args.command {
+            let started = std::time::Instant::now();
+            let results = battery::run(&battery::standard(), &mut values);
+            battery::summary(std::io::stdout(), &name, &results, started).unwrap();
+            battery_results.extend(battery::results(&name, &results));
+            continue;
+        }
+
+        let result = rank_test(&args, &name, extract, values);
+        let label = if count > 1 {
+            name
+        } else {
+            "observed".to_string()
+        };
+        observed.push((label, result.distribution()));
+        results.push(result);
+    }
+
+    if let Some(path) = &args.json {
+        let written = File::create(path)
+            .map_err(|err| err.to_string())
+            .and_then(|file| {
+                if let Some(Command::Battery) = args.command {
+                    serde_json::to_writer_pretty(file, &battery_results)
+                } else {
+                    serde_json::to_writer_pretty(file, &results)
+                }
+                .map_err(|err| err.to_string())
+            });
+        if let Err(err) = written {
+            eprintln!("failed to write {}: {}", path.display(), err);
+            std::process::exit(2);
+        }
+    }
+
+    if !observed.is_empty() && !args.no_plot {
+        let options = PlotOptions {
+            path: args.plot.clone(),
+            size: args.plot_size,
+            log_scale: args.log_scale,
+        };
+        let expected = rank_distribution(args.rows, args.cols);
+        if let Err(err) = plot(&options, &expected, &observed) {
+            eprintln!("failed to plot to {}: {}", args.plot.display(), err);
+            std::process::exit(2);
+        }
+    }
 }
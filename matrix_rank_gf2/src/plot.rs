@@ -1,45 +1,167 @@
-use crate::expected_rank_distribution;
-use plotters::prelude::*;
+use std::{error::Error, path::PathBuf};
+
+use plotters::{
+    coord::{Shift, ranged1d::ValueFormatter, types::RangedCoordf64},
+    prelude::*,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotOptions {
+    /// Written as SVG if the extension is `.svg`, as PNG otherwise.
+    pub path: PathBuf,
+    pub size: (u32, u32),
+    /// Log scale y-axis, showing the tail probabilities which are flat zero on a linear one.
+    pub log_scale: bool,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("1.png"),
+            size: (640, 480),
+            log_scale: false,
+        }
+    }
+}
+
+const COLORS: [RGBColor; 6] = [BLUE, GREEN, MAGENTA, CYAN, BLACK, YELLOW];
+
+/// Plots the `expected` rank distribution with each `observed` one, given with its label.
+pub fn plot(
+    options: &PlotOptions,
+    expected: &[f64],
+    observed: &[(String, Vec<f64>)],
+) -> Result<(), Box<dyn Error>> {
+    let is_svg = options
+        .path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+
+    if is_svg {
+        let root = SVGBackend::new(&options.path, options.size).into_drawing_area();
+        draw(root, options, expected, observed)
+    } else {
+        let root = BitMapBackend::new(&options.path, options.size).into_drawing_area();
+        draw(root, options, expected, observed)
+    }
+}
+
+fn draw<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    options: &PlotOptions,
+    expected: &[f64],
+    observed: &[(String, Vec<f64>)],
+) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let max_rank = (expected.len() - 1) as f64;
 
-pub fn plot(actual: &[f32]) -> Result<(), Box<dyn std::error::Error>> {
-    let root = BitMapBackend::new("1.png", (640, 480)).into_drawing_area();
     root.fill(&WHITE)?;
-    let mut chart = ChartBuilder::on(&root)
+    let mut builder = ChartBuilder::on(&root);
+    builder
         .caption("Rank Distribution", ("sans-serif", 50).into_font())
         .margin(5)
         .x_label_area_size(30)
-        .y_label_area_size(30)
-        .build_cartesian_2d(0f32..32f32, 0f32..1f32)?;
+        .y_label_area_size(if options.log_scale { 60 } else { 30 });
+
+    if options.log_scale {
+        // A decade below the rarest observed rank, so that ranks never seen are left out
+        let y_min = observed
+            .iter()
+            .flat_map(|(_, dist)| dist.iter().copied())
+            .filter(|&p| p > 0.0)
+            .fold(1.0, f64::min)
+            .max(1e-12)
+            / 10.0;
+        let chart = builder.build_cartesian_2d(0f64..max_rank, (y_min..1f64).log_scale())?;
+        draw_series(chart, expected, observed, y_min)?;
+    } else {
+        let chart = builder.build_cartesian_2d(0f64..max_rank, 0f64..1f64)?;
+        draw_series(chart, expected, observed, 0.0)?;
+    }
+
+    root.present()?;
+    Ok(())
+}
+
+/// Legend entry of a series drawn with `color`.
+fn legend(color: RGBColor) -> impl Fn((i32, i32)) -> PathElement<(i32, i32)> {
+    move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(3))
+}
 
+fn draw_series<'a, DB, Y>(
+    mut chart: ChartContext<'a, DB, Cartesian2d<RangedCoordf64, Y>>,
+    expected: &[f64],
+    observed: &[(String, Vec<f64>)],
+    y_min: f64,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+    Y: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
     chart.configure_mesh().draw()?;
 
-    chart
-        .draw_series(
-            LineSeries::new(
-                expected_rank_distribution()
-                    .iter()
-                    .enumerate()
-                    .map(|(x, y)| (x as f32, *y as f32)),
-                RED.filled(),
-            )
-            .point_size(4),
-        )?
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
+    let points = |dist: &[f64]| {
+        dist.iter()
+            .enumerate()
+            .filter(|(_, p)| **p >= y_min)
+            .map(|(x, p)| (x as f64, *p))
+            .collect::<Vec<_>>()
+    };
 
     chart
-        .draw_series(
-            LineSeries::new(
-                actual
-                    .iter()
-                    .enumerate()
-                    .map(|(x, y)| (x as f32, *y as f32)),
-                BLUE.filled(),
-            )
-            .point_size(4),
-        )?
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLUE));
+        .draw_series(LineSeries::new(points(expected), RED.filled()).point_size(4))?
+        .label("expected")
+        .legend(legend(RED));
 
-    root.present()?;
+    for (i, (label, dist)) in observed.iter().enumerate() {
+        let color = COLORS[i % COLORS.len()];
+        chart
+            .draw_series(LineSeries::new(points(dist), color.filled()).point_size(4))?
+            .label(label)
+            .legend(legend(color));
+    }
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperLeft)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rank_distribution;
+
+    #[test]
+    fn test_svg_legend() {
+        let path = std::env::temp_dir().join(format!("plot-{}.svg", std::process::id()));
+        let expected = rank_distribution(8, 8);
+        let observed = vec![
+            ("observed".to_string(), expected.clone()),
+            ("xorshift32".to_string(), vec![0.0; expected.len()]),
+        ];
+
+        for log_scale in [false, true] {
+            let options = PlotOptions {
+                path: path.clone(),
+                size: (320, 240),
+                log_scale,
+            };
+            plot(&options, &expected, &observed).unwrap();
+
+            let svg = std::fs::read_to_string(&path).unwrap();
+            assert!(svg.starts_with("<svg"));
+            for label in ["expected", "observed", "xorshift32"] {
+                assert!(svg.contains(label), "{} missing", label);
+            }
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+}
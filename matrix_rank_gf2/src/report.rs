@@ -0,0 +1,173 @@
+//! Results files and a markdown report of rank tests over several generators and seeds.
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::runner::RankResult;
+
+/// One rank test of a generator with a seed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Run {
+    pub seed: u64,
+    #[serde(flatten)]
+    pub result: RankResult,
+}
+
+/// Writes one line per run with its histogram as the `rank_*` columns.
+///
+/// As in the JSON results, the elapsed time is left out so that a rerun writes the same
+/// file. `write_timings` writes it apart.
+pub fn write_csv(mut w: impl Write, runs: &[Run]) -> io::Result<()> {
+    let ranks = runs.first().map_or(0, |run| run.result.counts.len());
+    write!(w, "generator,seed,matrices,chi_square,dof,p_value,verdict")?;
+    for rank in 0..ranks {
+        write!(w, ",rank_{}", rank)?;
+    }
+    writeln!(w)?;
+
+    for Run { seed, result } in runs {
+        write!(
+            w,
+            "{},{},{},{},{},{:e},{}",
+            result.name,
+            seed,
+            result.matrices,
+            result.chi_square,
+            result.dof,
+            result.p_value,
+            result.verdict
+        )?;
+        for count in &result.counts {
+            write!(w, ",{}", count)?;
+        }
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+/// Writes the elapsed time of each run, which changes from one run to the next.
+pub fn write_timings(mut w: impl Write, runs: &[Run]) -> io::Result<()> {
+    writeln!(w, "generator,seed,seconds")?;
+    for Run { seed, result } in runs {
+        writeln!(w, "{},{},{:.3}", result.name, seed, result.seconds)?;
+    }
+    Ok(())
+}
+
+/// Writes a summary table of `runs` followed by the plot of each generator, given as
+/// `(generator, image path relative to the report)`.
+pub fn write_markdown(
+    mut w: impl Write,
+    runs: &[Run],
+    plots: &[(String, String)],
+) -> io::Result<()> {
+    if let Some(Run { result, .. }) = runs.first() {
+        writeln!(w, "# Rank test of {}x{} matrices", result.rows, result.cols)?;
+        writeln!(w)?;
+        writeln!(w, "{} matrices per run.", result.trials)?;
+        writeln!(w)?;
+    }
+
+    writeln!(
+        w,
+        "| Generator | Seed | Matrices | Chi-square | dof | p-value | Verdict |"
+    )?;
+    writeln!(w, "| --- | ---: | ---: | ---: | ---: | ---: | --- |")?;
+    for Run { seed, result } in runs {
+        writeln!(
+            w,
+            "| {} | {} | {} | {:.4} | {} | {:.4e} | {} |",
+            result.name,
+            seed,
+            result.matrices,
+            result.chi_square,
+            result.dof,
+            result.p_value,
+            result.verdict
+        )?;
+    }
+
+    for (generator, image) in plots {
+        writeln!(w)?;
+        writeln!(w, "## {}", generator)?;
+        writeln!(w)?;
+        writeln!(w, "![{}]({})", generator, image)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(name: &str, seed: u64, counts: Vec<usize>, p_value: f64) -> Run {
+        Run {
+            seed,
+            result: RankResult {
+                name: name.to_string(),
+                rows: 2,
+                cols: 2,
+                trials: 16,
+                matrices: counts.iter().sum(),
+                stopped_early: false,
+                counts,
+                expected: vec![1.0 / 16.0, 9.0 / 16.0, 6.0 / 16.0],
+                classes: vec![0..2, 2..3],
+                chi_square: 1.5,
+                dof: 1,
+                p_value,
+                verdict: "PASS".to_string(),
+                seconds: 0.25,
+            },
+        }
+    }
+
+    #[test]
+    fn test_csv() {
+        let runs = [
+            run("a", 1, vec![1, 9, 6], 0.5),
+            run("b", 2, vec![0, 8, 8], 0.125),
+        ];
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &runs).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "generator,seed,matrices,chi_square,dof,p_value,verdict,rank_0,rank_1,rank_2\n\
+             a,1,16,1.5,1,5e-1,PASS,1,9,6\n\
+             b,2,16,1.5,1,1.25e-1,PASS,0,8,8\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown() {
+        let runs = [run("a", 1, vec![1, 9, 6], 0.5)];
+        let plots = [("a".to_string(), "a.png".to_string())];
+        let mut md = Vec::new();
+        write_markdown(&mut md, &runs, &plots).unwrap();
+        let md = String::from_utf8(md).unwrap();
+        assert!(md.starts_with("# Rank test of 2x2 matrices\n"));
+        assert!(md.contains("| a | 1 | 16 | 1.5000 | 1 | 5.0000e-1 | PASS |\n"));
+        assert!(md.ends_with("## a\n\n![a](a.png)\n"));
+    }
+
+    #[test]
+    fn test_json_flattens_the_result() {
+        let json = serde_json::to_value(run("a", 7, vec![1, 9, 6], 0.5)).unwrap();
+        assert_eq!(json["seed"], 7);
+        assert_eq!(json["name"], "a");
+        assert_eq!(json["counts"][1], 9);
+        assert!(json.get("seconds").is_none());
+    }
+
+    #[test]
+    fn test_timings() {
+        let runs = [run("a", 1, vec![1, 9, 6], 0.5)];
+        let mut csv = Vec::new();
+        write_timings(&mut csv, &runs).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "generator,seed,seconds\na,1,0.250\n"
+        );
+    }
+}
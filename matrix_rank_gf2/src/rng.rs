@@ -0,0 +1,431 @@
+//! Pseudo random number generators to test without piping an external program to stdin.
+//!
+//! Every generator is turned into the same byte stream as stdin by [`Stream`]: outputs are
+//! written in little endian, 4 bytes each for 32-bit generators and 8 for 64-bit ones.
+
+use std::io::{self, Read};
+
+pub trait Generator {
+    /// Number of bits of each output, 32 or 64.
+    fn bits(&self) -> u32;
+
+    /// Next output, in the low `bits()` bits.
+    fn next(&mut self) -> u64;
+}
+
+/// Expands `seed` into state words so that nearby seeds give unrelated, non-zero states.
+fn seed_words<const N: usize>(seed: u64) -> [u64; N] {
+    let mut splitmix = SplitMix64::new(seed);
+    std::array::from_fn(|_| {
+        loop {
+            let word = splitmix.next();
+            if word != 0 {
+                break word;
+            }
+        }
+    })
+}
+
+/// Marsaglia's xorshift with 32 bits of state and shifts (13, 17, 5).
+#[derive(Debug, Clone)]
+pub struct Xorshift32(u32);
+
+impl Xorshift32 {
+    pub fn new(state: u32) -> Self {
+        assert_ne!(state, 0);
+        Self(state)
+    }
+}
+
+impl Generator for Xorshift32 {
+    fn bits(&self) -> u32 {
+        32
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as u64
+    }
+}
+
+/// Marsaglia's xorshift with 64 bits of state and shifts (13, 7, 17).
+#[derive(Debug, Clone)]
+pub struct Xorshift64(u64);
+
+impl Xorshift64 {
+    pub fn new(state: u64) -> Self {
+        assert_ne!(state, 0);
+        Self(state)
+    }
+}
+
+impl Generator for Xorshift64 {
+    fn bits(&self) -> u32 {
+        64
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// Marsaglia's xorshift128 with 32-bit outputs and shifts (11, 8, 19).
+#[derive(Debug, Clone)]
+pub struct Xorshift128([u32; 4]);
+
+impl Xorshift128 {
+    pub fn new(state: [u32; 4]) -> Self {
+        assert_ne!(state, [0; 4]);
+        Self(state)
+    }
+}
+
+impl Generator for Xorshift128 {
+    fn bits(&self) -> u32 {
+        32
+    }
+
+    fn next(&mut self) -> u64 {
+        let [x, y, z, w] = self.0;
+        let t = x ^ (x << 11);
+        let next = w ^ (w >> 19) ^ t ^ (t >> 8);
+        self.0 = [y, z, w, next];
+        next as u64
+    }
+}
+
+/// xorshift64* of Vigna, whose output is scrambled by a multiplication.
+#[derive(Debug, Clone)]
+pub struct Xorshift64Star(u64);
+
+impl Xorshift64Star {
+    pub fn new(state: u64) -> Self {
+        assert_ne!(state, 0);
+        Self(state)
+    }
+}
+
+impl Generator for Xorshift64Star {
+    fn bits(&self) -> u32 {
+        64
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+/// xorshift128+ of Vigna with shifts (23, 18, 5), whose lowest bits are known to fail
+/// the rank test.
+#[derive(Debug, Clone)]
+pub struct Xorshift128Plus([u64; 2]);
+
+impl Xorshift128Plus {
+    pub fn new(state: [u64; 2]) -> Self {
+        assert_ne!(state, [0; 2]);
+        Self(state)
+    }
+}
+
+impl Generator for Xorshift128Plus {
+    fn bits(&self) -> u32 {
+        64
+    }
+
+    fn next(&mut self) -> u64 {
+        let [mut s1, s0] = self.0;
+        let result = s0.wrapping_add(s1);
+        s1 ^= s1 << 23;
+        self.0 = [s0, s1 ^ s0 ^ (s1 >> 18) ^ (s0 >> 5)];
+        result
+    }
+}
+
+/// PCG32 (XSH RR) of O'Neill, with 64 bits of state and a selectable stream.
+#[derive(Debug, Clone)]
+pub struct Pcg32 {
+    state: u64,
+    inc: u64,
+}
+
+impl Pcg32 {
+    const MULTIPLIER: u64 = 6364136223846793005;
+
+    /// Same as `pcg32_srandom_r(initstate, initseq)` of the reference implementation.
+    pub fn new(initstate: u64, initseq: u64) -> Self {
+        let mut pcg = Self {
+            state: 0,
+            inc: (initseq << 1) | 1,
+        };
+        pcg.step();
+        pcg.state = pcg.state.wrapping_add(initstate);
+        pcg.step();
+        pcg
+    }
+
+    fn step(&mut self) {
+        self.state = self
+            .state
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(self.inc);
+    }
+}
+
+impl Generator for Pcg32 {
+    fn bits(&self) -> u32 {
+        32
+    }
+
+    fn next(&mut self) -> u64 {
+        let old = self.state;
+        self.step();
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+        xorshifted.rotate_right(rot) as u64
+    }
+}
+
+/// SplitMix64 of Steele, Lea and Flood, also used to seed the other generators.
+#[derive(Debug, Clone)]
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub fn new(state: u64) -> Self {
+        Self(state)
+    }
+}
+
+impl Generator for SplitMix64 {
+    fn bits(&self) -> u32 {
+        64
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Linear congruential generator `x = (a * x + c) mod m` outputting `(x >> shift)` as
+/// 32 bits, where `m = 0` stands for 2^64.
+#[derive(Debug, Clone)]
+pub struct Lcg {
+    state: u64,
+    a: u64,
+    c: u64,
+    m: u64,
+    shift: u32,
+}
+
+impl Lcg {
+    pub fn new(state: u64, a: u64, c: u64, m: u64, shift: u32) -> Self {
+        let state = if m == 0 { state } else { state % m };
+        Self {
+            state,
+            a,
+            c,
+            m,
+            shift,
+        }
+    }
+
+    /// MINSTD of Park and Miller, `a = 16807` modulo 2^31 - 1. Bit 31 is always 0.
+    pub fn minstd(seed: u64) -> Self {
+        Self::new(seed % 0x7fff_fffe + 1, 16807, 0, 0x7fff_ffff, 0)
+    }
+
+    /// IBM's infamous RANDU, `a = 65539` modulo 2^31. Bit 31 is always 0.
+    pub fn randu(seed: u64) -> Self {
+        Self::new(seed | 1, 65539, 0, 1 << 31, 0)
+    }
+
+    /// Knuth's MMIX LCG modulo 2^64, outputting the high 32 bits.
+    pub fn mmix(seed: u64) -> Self {
+        Self::new(seed, 6364136223846793005, 1442695040888963407, 0, 32)
+    }
+}
+
+impl Generator for Lcg {
+    fn bits(&self) -> u32 {
+        32
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state = if self.m == 0 {
+            self.state.wrapping_mul(self.a).wrapping_add(self.c)
+        } else {
+            ((self.state as u128 * self.a as u128 + self.c as u128) % self.m as u128) as u64
+        };
+        (self.state >> self.shift) & 0xffff_ffff
+    }
+}
+
+/// Byte stream of the outputs of a generator.
+pub struct Stream<G> {
+    generator: G,
+    buf: [u8; 8],
+    pos: usize,
+}
+
+impl<G: Generator> Stream<G> {
+    pub fn new(generator: G) -> Self {
+        Self {
+            generator,
+            buf: [0; 8],
+            pos: 8,
+        }
+    }
+}
+
+impl<G: Generator> Read for Stream<G> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = (self.generator.bits() / 8) as usize;
+        for b in buf.iter_mut() {
+            if self.pos >= len {
+                self.buf = self.generator.next().to_le_bytes();
+                self.pos = 0;
+            }
+            *b = self.buf[self.pos];
+            self.pos += 1;
+        }
+        Ok(buf.len())
+    }
+}
+
+/// Names accepted by [`open`].
+pub const NAMES: &[&str] = &[
+    "xorshift32",
+    "xorshift64",
+    "xorshift128",
+    "xorshift64star",
+    "xorshift128plus",
+    "pcg32",
+    "splitmix64",
+    "minstd",
+    "randu",
+    "lcg64",
+    "urandom",
+];
+
+/// Byte stream of the generator called `name`, seeded by `seed`.
+///
+/// `urandom` reads `/dev/urandom` and ignores `seed`.
+pub fn open(name: &str, seed: u64) -> io::Result<Box<dyn Read + Send>> {
+    fn stream(generator: impl Generator + Send + 'static) -> Box<dyn Read + Send> {
+        Box::new(Stream::new(generator))
+    }
+
+    Ok(match name {
+        "xorshift32" => stream(Xorshift32::new(seed_words::<1>(seed)[0] as u32 | 1)),
+        "xorshift64" => stream(Xorshift64::new(seed_words::<1>(seed)[0])),
+        "xorshift128" => {
+            let [a, b] = seed_words(seed);
+            stream(Xorshift128::new([
+                a as u32,
+                (a >> 32) as u32,
+                b as u32,
+                (b >> 32) as u32,
+            ]))
+        }
+        "xorshift64star" => stream(Xorshift64Star::new(seed_words::<1>(seed)[0])),
+        "xorshift128plus" => stream(Xorshift128Plus::new(seed_words(seed))),
+        "pcg32" => stream(Pcg32::new(seed, 54)),
+        "splitmix64" => stream(SplitMix64::new(seed)),
+        "minstd" => stream(Lcg::minstd(seed)),
+        "randu" => stream(Lcg::randu(seed)),
+        "lcg64" => stream(Lcg::mmix(seed)),
+        "urandom" => Box::new(std::fs::File::open("/dev/urandom")?),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unknown generator {}", name),
+            ));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outputs(mut generator: impl Generator, n: usize) -> Vec<u64> {
+        (0..n).map(|_| generator.next()).collect()
+    }
+
+    #[test]
+    fn test_reference_outputs() {
+        // Values from the papers and reference implementations of each generator
+        assert_eq!(outputs(Xorshift32::new(2463534242), 1), [723471715]);
+        assert_eq!(
+            outputs(Xorshift64::new(88172645463325252), 1),
+            [8748534153485358512]
+        );
+        assert_eq!(
+            outputs(
+                Xorshift128::new([123456789, 362436069, 521288629, 88675123]),
+                5
+            ),
+            [3701687786, 458299110, 2500872618, 3633119408, 516391518]
+        );
+        assert_eq!(
+            outputs(Xorshift64Star::new(88172645463325252), 3),
+            [
+                16620430977058721579,
+                12052379865695375093,
+                11451962570540714196
+            ]
+        );
+        assert_eq!(
+            outputs(Xorshift128Plus::new([1, 2]), 4),
+            [3, 8388645, 33816707, 70368778527840]
+        );
+        assert_eq!(
+            outputs(Pcg32::new(42, 54), 6),
+            [
+                0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e
+            ]
+        );
+        assert_eq!(outputs(SplitMix64::new(0), 1), [0xe220a8397b1dcdaf]);
+        assert_eq!(outputs(Lcg::minstd(0), 2), [16807, 282475249]);
+        assert_eq!(outputs(Lcg::randu(0), 3), [65539, 393225, 1769499]);
+        assert_eq!(outputs(Lcg::mmix(0), 3), [335903614, 436792849, 2599843874]);
+    }
+
+    #[test]
+    fn test_stream() {
+        let mut bytes = [0; 12];
+        Stream::new(SplitMix64::new(0))
+            .read_exact(&mut bytes)
+            .unwrap();
+        assert_eq!(bytes[..8], 0xe220a8397b1dcdafu64.to_le_bytes());
+
+        // Reads of odd sizes continue where the previous one stopped
+        let mut stream = Stream::new(Xorshift32::new(2463534242));
+        let (mut a, mut b) = ([0; 3], [0; 5]);
+        stream.read_exact(&mut a).unwrap();
+        stream.read_exact(&mut b).unwrap();
+        assert_eq!(a, 723471715u32.to_le_bytes()[..3]);
+        assert_eq!(b[0], 723471715u32.to_le_bytes()[3]);
+    }
+
+    #[test]
+    fn test_open() {
+        for name in NAMES {
+            let mut bytes = [0; 16];
+            open(name, 1).unwrap().read_exact(&mut bytes).unwrap();
+            assert_ne!(bytes, [0; 16], "{}", name);
+        }
+        assert!(open("mt19937", 1).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! Rank test runs over a stream, with progress reports, early stopping and a
+//! machine-readable result.
+
+use std::{
+    ops::{ControlFlow, Range},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use crate::{
+    rank_distribution,
+    stats::{ChiSquare, MIN_EXPECTED, chi_square},
+    trials::{Shape, rank_counts_with},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub shape: Shape,
+    pub trials: usize,
+    pub threads: usize,
+    /// How often to report progress, if at all.
+    pub progress: Option<Duration>,
+    /// Stop as soon as the running p-value is below this.
+    ///
+    /// The p-value is checked after every chunk of matrices, so a false alarm is far more
+    /// likely than the threshold alone says. It should be well below the level a final
+    /// result is judged at, like the 1e-10 of a FAIL.
+    pub stop_below: Option<f64>,
+}
+
+/// State of a run when progress is reported.
+#[derive(Debug)]
+pub struct Progress<'a> {
+    pub matrices: usize,
+    pub elapsed: Duration,
+    pub counts: &'a [usize],
+    pub chi_square: &'a ChiSquare,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RankResult {
+    pub name: String,
+    pub rows: usize,
+    pub cols: usize,
+    /// Number of matrices requested
+    pub trials: usize,
+    /// Number of matrices tested, fewer than `trials` if the input ran out or the run
+    /// stopped early
+    pub matrices: usize,
+    pub stopped_early: bool,
+    /// Number of matrices of each rank
+    pub counts: Vec<usize>,
+    /// Probability of each rank
+    pub expected: Vec<f64>,
+    /// Ranges of ranks merged into one class of the chi-square test
+    pub classes: Vec<Range<usize>>,
+    pub chi_square: f64,
+    pub dof: usize,
+    pub p_value: f64,
+    pub verdict: String,
+    /// Wall-clock time of the run, left out of the JSON so that a rerun writes the same file
+    #[serde(skip)]
+    pub seconds: f64,
+}
+
+impl RankResult {
+    /// Observed probability of each rank.
+    pub fn distribution(&self) -> Vec<f64> {
+        self.counts
+            .iter()
+            .map(|&c| c as f64 / self.matrices.max(1) as f64)
+            .collect()
+    }
+}
+
+/// Runs the rank test on `values` named `name`, calling `report` every
+/// `options.progress`.
+pub fn run(
+    name: &str,
+    options: &Options,
+    values: &mut (impl Iterator<Item = u32> + ?Sized),
+    mut report: impl FnMut(&Progress),
+) -> RankResult {
+    let Shape { rows, cols, .. } = options.shape;
+    let expected = rank_distribution(rows, cols);
+    let started = Instant::now();
+    let mut last_report = started;
+    let mut stopped_early = false;
+
+    let counts = rank_counts_with(
+        options.shape,
+        values,
+        options.trials,
+        options.threads,
+        |counts| {
+            let now = Instant::now();
+            let due = options
+                .progress
+                .is_some_and(|interval| now - last_report >= interval);
+            if !due && options.stop_below.is_none() {
+                return ControlFlow::Continue(());
+            }
+
+            let result = chi_square(counts, &expected, MIN_EXPECTED);
+            if due {
+                report(&Progress {
+                    matrices: counts.iter().sum(),
+                    elapsed: now - started,
+                    counts,
+                    chi_square: &result,
+                });
+                last_report = now;
+            }
+            // Too few matrices leave a single class and no test at all
+            if let Some(threshold) = options.stop_below
+                && result.dof > 0
+                && result.p_value < threshold
+            {
+                stopped_early = true;
+                return ControlFlow::Break(());
+            }
+            ControlFlow::Continue(())
+        },
+    );
+
+    let result = chi_square(&counts, &expected, MIN_EXPECTED);
+    RankResult {
+        name: name.to_string(),
+        rows,
+        cols,
+        trials: options.trials,
+        matrices: counts.iter().sum(),
+        stopped_early,
+        counts,
+        expected,
+        classes: result.classes.clone(),
+        chi_square: result.statistic,
+        dof: result.dof,
+        p_value: result.p_value,
+        verdict: result.verdict().to_string(),
+        seconds: started.elapsed().as_secs_f64(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{Generator, SplitMix64, Xorshift32};
+
+    fn options(trials: usize, stop_below: Option<f64>) -> Options {
+        Options {
+            shape: Shape {
+                rows: 32,
+                cols: 32,
+                bits: 32,
+            },
+            trials,
+            threads: 2,
+            progress: Some(Duration::ZERO),
+            stop_below,
+        }
+    }
+
+    #[test]
+    fn test_early_stop() {
+        // 32 consecutive states of xorshift32 are linearly independent, so every matrix has
+        // full rank where 29% are expected to
+        let mut generator = Xorshift32::new(1);
+        let mut values = std::iter::from_fn(|| Some(generator.next() as u32));
+        let mut reports = 0;
+        let result = run(
+            "xorshift32",
+            &options(100_000, Some(1e-10)),
+            &mut values,
+            |p| {
+                assert!(p.matrices > 0);
+                reports += 1;
+            },
+        );
+        assert!(result.stopped_early);
+        assert!(result.matrices < 100_000);
+        assert!(result.p_value < 1e-10);
+        assert_eq!(result.verdict, "FAIL");
+        assert!(reports > 0);
+
+        let mut generator = SplitMix64::new(1);
+        let mut values = std::iter::from_fn(|| Some(generator.next() as u32));
+        let result = run(
+            "splitmix64",
+            &options(20_000, Some(1e-10)),
+            &mut values,
+            |_| {},
+        );
+        assert!(!result.stopped_early);
+        assert_eq!(result.matrices, 20_000);
+        assert_eq!(result.distribution().iter().sum::<f64>().round(), 1.0);
+    }
+
+    #[test]
+    fn test_json() {
+        let mut values = 0..32 * 100;
+        let result = run("counter", &options(1000, None), &mut values, |_| {});
+        assert_eq!(result.matrices, 100);
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["name"], "counter");
+        assert_eq!(json["trials"], 1000);
+        assert_eq!(json["matrices"], 100);
+        assert_eq!(json["stopped_early"], false);
+        assert_eq!(json["counts"].as_array().unwrap().len(), 33);
+    }
+}
@@ -0,0 +1,319 @@
+use std::ops::Range;
+
+/// Classes with fewer expected observations than this are merged with their neighbours
+/// before computing the chi-square statistic, same as `gofs_MinExpected` of TestU01.
+pub const MIN_EXPECTED: f64 = 10.0;
+
+/// Natural log of the gamma function, by the Lanczos approximation.
+pub fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // Reflection formula
+        return (std::f64::consts::PI / (std::f64::consts::PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let mut sum = COEFFICIENTS[0];
+    for (i, c) in COEFFICIENTS.iter().enumerate().skip(1) {
+        sum += c / (x + i as f64);
+    }
+    let t = x + G + 0.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Regularized upper incomplete gamma function `Q(a, x) = Γ(a, x) / Γ(a)`.
+pub fn gamma_q(a: f64, x: f64) -> f64 {
+    const EPS: f64 = 1e-15;
+    const MAX_ITER: usize = 10_000;
+
+    if x <= 0.0 {
+        return 1.0;
+    }
+
+    let ln_prefix = a * x.ln() - x - ln_gamma(a);
+
+    if x < a + 1.0 {
+        // Series of P(a, x)
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        for n in 1..MAX_ITER {
+            term *= x / (a + n as f64);
+            sum += term;
+            if term.abs() < sum.abs() * EPS {
+                break;
+            }
+        }
+        1.0 - sum * ln_prefix.exp()
+    } else {
+        // Continued fraction of Q(a, x) by the modified Lentz's method
+        let tiny = f64::MIN_POSITIVE / EPS;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..MAX_ITER {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < EPS {
+                break;
+            }
+        }
+        ln_prefix.exp() * h
+    }
+}
+
+/// Probability that a chi-square variable with `dof` degrees of freedom exceeds `x`.
+pub fn chi_square_sf(x: f64, dof: usize) -> f64 {
+    gamma_q(dof as f64 / 2.0, x / 2.0)
+}
+
+/// Complementary error function, `erfc(x) = Q(1/2, x^2)` for `x >= 0`.
+pub fn erfc(x: f64) -> f64 {
+    if x >= 0.0 {
+        gamma_q(0.5, x * x)
+    } else {
+        2.0 - gamma_q(0.5, x * x)
+    }
+}
+
+/// Probability that a Poisson variable of mean `lambda` is at least `k`.
+pub fn poisson_sf(k: usize, lambda: f64) -> f64 {
+    if k == 0 {
+        1.0
+    } else {
+        // P(X >= k) is the regularized lower incomplete gamma function P(k, lambda)
+        1.0 - gamma_q(k as f64, lambda)
+    }
+}
+
+/// Groups adjacent classes so that each group expects at least `min_expected` observations.
+///
+/// Like `gofs_MergeClasses` of TestU01, classes are merged from both tails towards the
+/// mode, which for rank distributions lumps all low ranks together.
+pub fn merge_classes(expected: &[f64], min_expected: f64) -> Vec<Range<usize>> {
+    let mode = expected
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+
+    let mut lower = Vec::new();
+    let mut start = 0;
+    let mut sum = 0.0;
+    for (i, e) in expected.iter().enumerate().take(mode) {
+        sum += e;
+        if sum >= min_expected {
+            lower.push(start..i + 1);
+            start = i + 1;
+            sum = 0.0;
+        }
+    }
+
+    let mut upper = Vec::new();
+    let mut end = expected.len();
+    let mut sum = 0.0;
+    for (i, e) in expected.iter().enumerate().skip(mode + 1).rev() {
+        sum += e;
+        if sum >= min_expected {
+            upper.push(i..end);
+            end = i;
+            sum = 0.0;
+        }
+    }
+
+    // Leftovers of each tail go to the class of the mode
+    let mut classes = lower;
+    classes.push(start..end);
+    classes.extend(upper.into_iter().rev());
+    classes
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChiSquare {
+    pub statistic: f64,
+    pub dof: usize,
+    pub p_value: f64,
+    /// Ranges of original classes which were merged into one
+    pub classes: Vec<Range<usize>>,
+}
+
+/// Pearson's chi-square goodness-of-fit test of `observed` counts against `probabilities`.
+pub fn chi_square(observed: &[usize], probabilities: &[f64], min_expected: f64) -> ChiSquare {
+    assert_eq!(observed.len(), probabilities.len());
+
+    let n = observed.iter().sum::<usize>() as f64;
+    let expected = probabilities.iter().map(|p| p * n).collect::<Vec<_>>();
+    let classes = merge_classes(&expected, min_expected);
+
+    let statistic = classes
+        .iter()
+        .map(|class| {
+            let o = observed[class.clone()].iter().sum::<usize>() as f64;
+            let e = expected[class.clone()].iter().sum::<f64>();
+            (o - e).powi(2) / e
+        })
+        .sum::<f64>();
+    let dof = classes.len().saturating_sub(1);
+
+    ChiSquare {
+        statistic,
+        dof,
+        p_value: chi_square_sf(statistic, dof),
+        classes,
+    }
+}
+
+impl ChiSquare {
+    /// Verdict on the p-value, unless too few observations merged all classes into one and
+    /// left no degree of freedom to test.
+    pub fn verdict(&self) -> Verdict {
+        if self.dof == 0 {
+            Verdict::InsufficientData
+        } else {
+            Verdict::of(self.p_value)
+        }
+    }
+}
+
+/// Interpretation of a p-value following TestU01's conventions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    /// Outside of [0.001, 0.999]
+    Suspicious,
+    /// Outside of [1e-10, 1 - 1e-10]
+    Fail,
+    /// Too few observations to compute a meaningful p-value
+    InsufficientData,
+}
+
+impl Verdict {
+    pub fn of(p_value: f64) -> Self {
+        if !(1e-10..=1.0 - 1e-10).contains(&p_value) {
+            Verdict::Fail
+        } else if !(0.001..=0.999).contains(&p_value) {
+            Verdict::Suspicious
+        } else {
+            Verdict::Pass
+        }
+    }
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "PASS"),
+            Verdict::Suspicious => write!(f, "SUSPICIOUS"),
+            Verdict::Fail => write!(f, "FAIL"),
+            Verdict::InsufficientData => write!(f, "INSUFFICIENT DATA"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expected_rank_distribution;
+
+    fn assert_close(a: f64, b: f64, eps: f64) {
+        assert!((a - b).abs() < eps, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_ln_gamma() {
+        assert_close(ln_gamma(1.0), 0.0, 1e-12);
+        assert_close(ln_gamma(5.0), 24f64.ln(), 1e-12);
+        assert_close(ln_gamma(0.5), std::f64::consts::PI.sqrt().ln(), 1e-12);
+        assert_close(ln_gamma(100.0), 359.134_205_369_575_4, 1e-9);
+    }
+
+    #[test]
+    fn test_chi_square_sf() {
+        // Critical values of the chi-square distribution at 5% and 0.1%
+        assert_close(chi_square_sf(3.841_458_820_694_124, 1), 0.05, 1e-9);
+        assert_close(chi_square_sf(18.307_038_053_275_146, 10), 0.05, 1e-9);
+        assert_close(chi_square_sf(16.266_236_196_238_13, 3), 0.001, 1e-9);
+        assert_close(chi_square_sf(0.0, 3), 1.0, 1e-12);
+        assert_close(chi_square_sf(2.0, 2), (-1f64).exp(), 1e-12);
+    }
+
+    #[test]
+    fn test_erfc() {
+        assert_close(erfc(0.0), 1.0, 1e-12);
+        assert_close(erfc(1.0), 0.157_299_207_050_285_13, 1e-12);
+        assert_close(erfc(-1.0), 1.842_700_792_949_715, 1e-12);
+        assert_close(erfc(3.0), 2.209_049_699_858_544e-5, 1e-15);
+    }
+
+    #[test]
+    fn test_poisson_sf() {
+        assert_close(poisson_sf(0, 2.0), 1.0, 1e-12);
+        assert_close(poisson_sf(1, 2.0), 1.0 - (-2f64).exp(), 1e-12);
+        assert_close(poisson_sf(3, 2.0), 1.0 - 5.0 * (-2f64).exp(), 1e-12);
+    }
+
+    #[test]
+    fn test_merge_classes() {
+        let expected = expected_rank_distribution().map(|p| p * 1_000_000.0);
+        assert_eq!(
+            merge_classes(&expected, MIN_EXPECTED),
+            vec![0..29, 29..30, 30..31, 31..32, 32..33]
+        );
+
+        let expected = expected_rank_distribution().map(|p| p * 1_000.0);
+        assert_eq!(
+            merge_classes(&expected, MIN_EXPECTED),
+            vec![0..31, 31..32, 32..33]
+        );
+
+        assert_eq!(
+            merge_classes(&[1.0, 20.0, 5.0, 5.0, 1.0], MIN_EXPECTED),
+            vec![0..2, 2..5]
+        );
+    }
+
+    #[test]
+    fn test_chi_square() {
+        let probabilities = [0.25, 0.5, 0.25];
+
+        let result = chi_square(&[250, 500, 250], &probabilities, MIN_EXPECTED);
+        assert_close(result.statistic, 0.0, 1e-12);
+        assert_eq!(result.dof, 2);
+        assert_close(result.p_value, 1.0, 1e-12);
+
+        let result = chi_square(&[300, 450, 250], &probabilities, MIN_EXPECTED);
+        assert_close(result.statistic, 15.0, 1e-12);
+        assert_close(result.p_value, (-7.5f64).exp(), 1e-12);
+        assert_eq!(Verdict::of(result.p_value), Verdict::Suspicious);
+        assert_eq!(result.verdict(), Verdict::Suspicious);
+
+        // All classes merge into one, whose p-value of 1 says nothing
+        let result = chi_square(&[1, 2, 1], &probabilities, MIN_EXPECTED);
+        assert_eq!(result.dof, 0);
+        assert_eq!(result.verdict(), Verdict::InsufficientData);
+    }
+}
@@ -0,0 +1,129 @@
+//! Transforms of the values before they fill matrices, for flaws which only show between
+//! outputs apart from each other or across streams, as in parallel setups.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Combine {
+    /// One value of each stream in turn
+    #[default]
+    Interleave,
+    /// The XOR of one value of each stream
+    Xor,
+}
+
+/// Combines `streams` into one, which ends with the first of them to end.
+pub fn combine<I: Iterator<Item = u32>>(
+    mut streams: Vec<I>,
+    combine: Combine,
+) -> impl Iterator<Item = u32> {
+    assert!(!streams.is_empty());
+    let mut next = 0;
+    std::iter::from_fn(move || match combine {
+        Combine::Interleave => {
+            let value = streams[next].next();
+            next = (next + 1) % streams.len();
+            value
+        }
+        Combine::Xor => streams
+            .iter_mut()
+            .try_fold(0, |acc, stream| Some(acc ^ stream.next()?)),
+    })
+}
+
+/// Every `k`-th value, starting with the first.
+pub fn decimate(values: impl Iterator<Item = u32>, k: usize) -> impl Iterator<Item = u32> {
+    values.step_by(k)
+}
+
+/// Reorders `values` so that the rows of each matrix are `d` rows apart in the original
+/// stream, with each row made of `per_row` values.
+///
+/// Each block of `d` matrices is read at once and regrouped, so no value is dropped but
+/// a trailing partial block.
+pub fn lag(
+    mut values: impl Iterator<Item = u32>,
+    d: usize,
+    rows: usize,
+    per_row: usize,
+) -> impl Iterator<Item = u32> {
+    let per_matrix = rows * per_row;
+    let block = d * per_matrix;
+    let mut buf = Vec::with_capacity(block);
+    let mut pos = block;
+    std::iter::from_fn(move || {
+        if pos == block {
+            buf.clear();
+            buf.extend((&mut values).take(block));
+            if buf.len() < block {
+                return None;
+            }
+            pos = 0;
+        }
+        // Value v of row r of matrix m of the block is from row r * d + m
+        let (m, r, v) = (pos / per_matrix, pos % per_matrix / per_row, pos % per_row);
+        pos += 1;
+        Some(buf[(r * d + m) * per_row + v])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        input::{Endian, values},
+        rng,
+        trials::{Shape, rank_counts},
+    };
+
+    #[test]
+    fn test_combine() {
+        let streams = || vec![vec![1, 2, 3].into_iter(), vec![10, 20].into_iter()];
+        assert_eq!(
+            combine(streams(), Combine::Interleave).collect::<Vec<_>>(),
+            [1, 10, 2, 20, 3]
+        );
+        assert_eq!(
+            combine(streams(), Combine::Xor).collect::<Vec<_>>(),
+            [11, 22]
+        );
+    }
+
+    #[test]
+    fn test_decimate() {
+        assert_eq!(decimate(0..10, 3).collect::<Vec<_>>(), [0, 3, 6, 9]);
+        assert_eq!(decimate(0..3, 1).collect::<Vec<_>>(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_lag() {
+        // Rows of 2 values, 2 rows per matrix and a lag of 3 rows, leaving out the last 8
+        // values short of a block
+        let rows = |values: Vec<u32>| values.chunks(2).map(|row| row[0] / 2).collect::<Vec<_>>();
+        let lagged = lag(0..20, 3, 2, 2).collect::<Vec<_>>();
+        assert_eq!(lagged.len(), 12);
+        assert_eq!(rows(lagged), [0, 3, 1, 4, 2, 5]);
+        assert_eq!(lag(0..4, 1, 2, 2).collect::<Vec<_>>(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_cross_stream_correlation() {
+        let shape = Shape {
+            rows: 32,
+            cols: 32,
+            bits: 32,
+        };
+        let stream = |seed| values(rng::open("pcg32", seed).unwrap(), Endian::Little);
+
+        // Streams seeded alike go unnoticed alone, but interleaved they repeat every row
+        // and combined by XOR they cancel out
+        let counts = rank_counts(shape, &mut stream(7), 100, 1);
+        assert_eq!(counts[..28].iter().sum::<usize>(), 0);
+        let mut interleaved = combine(vec![stream(7), stream(7)], Combine::Interleave);
+        assert_eq!(rank_counts(shape, &mut interleaved, 100, 1)[16], 100);
+        let mut xored = combine(vec![stream(7), stream(7)], Combine::Xor);
+        assert_eq!(rank_counts(shape, &mut xored, 100, 1)[0], 100);
+
+        let mut xored = combine(vec![stream(7), stream(8)], Combine::Xor);
+        let counts = rank_counts(shape, &mut xored, 100, 1);
+        assert_eq!(counts[..28].iter().sum::<usize>(), 0);
+    }
+}
@@ -0,0 +1,234 @@
+//! Running many rank trials on worker threads.
+
+use std::{
+    collections::BTreeMap,
+    ops::ControlFlow,
+    sync::{Mutex, mpsc},
+};
+
+use crate::bit_matrix::BitMatrix;
+
+/// Shape of the matrices of a trial and how many bits each value gives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shape {
+    pub rows: usize,
+    pub cols: usize,
+    pub bits: u32,
+}
+
+impl Shape {
+    /// Number of values consumed by one matrix.
+    pub fn values(&self) -> usize {
+        self.rows * self.cols.div_ceil(self.bits as usize)
+    }
+}
+
+/// Histogram of the ranks of up to `trials` matrices read from `values`, counted on
+/// `threads` worker threads.
+///
+/// Values are read in order on the calling thread and handed out as chunks of whole
+/// matrices, so the matrices, and thus the counts, do not depend on `threads`. If
+/// `values` runs out, only the complete matrices are counted.
+pub fn rank_counts(
+    shape: Shape,
+    values: &mut (impl Iterator<Item = u32> + ?Sized),
+    trials: usize,
+    threads: usize,
+) -> Vec<usize> {
+    rank_counts_with(
+        shape,
+        values,
+        trials,
+        threads,
+        |_| ControlFlow::Continue(()),
+    )
+}
+
+/// [`rank_counts`] which calls `progress` with the running counts after each chunk of
+/// matrices, and stops early once it breaks.
+///
+/// Chunks are merged in the order they were read whichever worker finishes first, so the
+/// running counts, and where a deterministic `progress` stops, do not depend on `threads`
+/// either.
+pub fn rank_counts_with(
+    shape: Shape,
+    values: &mut (impl Iterator<Item = u32> + ?Sized),
+    trials: usize,
+    threads: usize,
+    mut progress: impl FnMut(&[usize]) -> ControlFlow<()>,
+) -> Vec<usize> {
+    let per_matrix = shape.values();
+    let chunk = (1 << 16) / per_matrix.max(1) + 1;
+    let classes = shape.rows.min(shape.cols) + 1;
+    let mut counts = vec![0; classes];
+
+    let (sender, receiver) = mpsc::sync_channel::<(usize, Vec<u32>)>(2 * threads);
+    let receiver = Mutex::new(receiver);
+    let (result_sender, results) = mpsc::channel::<(usize, Vec<usize>)>();
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            let receiver = &receiver;
+            let result_sender = result_sender.clone();
+            scope.spawn(move || {
+                loop {
+                    // Not in a `while let`, which would hold the lock during the loop body
+                    let chunk = receiver.lock().unwrap().recv();
+                    let Ok((index, chunk)) = chunk else {
+                        break;
+                    };
+                    let mut counts = vec![0; classes];
+                    let mut values = chunk.into_iter();
+                    while let Some(mut matrix) =
+                        BitMatrix::from_bits(shape.rows, shape.cols, shape.bits, &mut values)
+                    {
+                        counts[matrix.rank()] += 1;
+                    }
+                    // Nobody listens any more after an early stop
+                    if result_sender.send((index, counts)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(result_sender);
+
+        // Counts of chunks finished ahead of an earlier one wait here
+        let mut pending = BTreeMap::new();
+        let mut next = 0;
+        let mut merge = |(index, chunk_counts): (usize, Vec<usize>)| {
+            pending.insert(index, chunk_counts);
+            while let Some(chunk_counts) = pending.remove(&next) {
+                next += 1;
+                for (total, count) in counts.iter_mut().zip(chunk_counts) {
+                    *total += count;
+                }
+                progress(&counts)?;
+            }
+            ControlFlow::Continue(())
+        };
+
+        let mut flow = ControlFlow::Continue(());
+        let mut sent = 0;
+        let mut remaining = trials;
+        while remaining > 0 && flow.is_continue() {
+            let n = chunk.min(remaining);
+            let mut buf = (&mut *values).take(n * per_matrix).collect::<Vec<_>>();
+            let complete = buf.len() / per_matrix.max(1);
+            remaining -= n;
+            if complete > 0 {
+                buf.truncate(complete * per_matrix);
+                sender.send((sent, buf)).unwrap();
+                sent += 1;
+            }
+            if complete < n {
+                break;
+            }
+            while flow.is_continue() {
+                let Ok(received) = results.try_recv() else {
+                    break;
+                };
+                flow = merge(received);
+            }
+        }
+        drop(sender);
+
+        while flow.is_continue() {
+            let Ok(received) = results.recv() else {
+                break;
+            };
+            flow = merge(received);
+        }
+        drop(results);
+    });
+
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{Generator, SplitMix64};
+
+    fn values(n: usize) -> Vec<u32> {
+        let mut generator = SplitMix64::new(7);
+        (0..n).map(|_| generator.next() as u32).collect()
+    }
+
+    #[test]
+    fn test_threads_agree() {
+        let shape = Shape {
+            rows: 6,
+            cols: 40,
+            bits: 5,
+        };
+        let values = values(shape.values() * 5000);
+
+        let mut expected = vec![0; 7];
+        let mut iter = values.iter().copied();
+        while let Some(mut matrix) = BitMatrix::from_bits(6, 40, 5, &mut iter) {
+            expected[matrix.rank()] += 1;
+        }
+        assert_eq!(expected.iter().sum::<usize>(), 5000);
+
+        for threads in [1, 2, 3, 8] {
+            let counts = rank_counts(shape, &mut values.iter().copied(), 5000, threads);
+            assert_eq!(counts, expected, "{} threads", threads);
+        }
+    }
+
+    #[test]
+    fn test_short_input() {
+        let shape = Shape {
+            rows: 32,
+            cols: 32,
+            bits: 32,
+        };
+        // 10 complete matrices and a partial one
+        let values = values(32 * 10 + 5);
+        let mut iter = values.iter().copied();
+        let counts = rank_counts(shape, &mut iter, 1000, 4);
+        assert_eq!(counts.iter().sum::<usize>(), 10);
+        assert_eq!(iter.next(), None);
+
+        // Values past the requested trials are left unread
+        let mut iter = values.iter().copied();
+        let counts = rank_counts(shape, &mut iter, 3, 2);
+        assert_eq!(counts.iter().sum::<usize>(), 3);
+        assert_eq!(iter.next(), Some(values[96]));
+    }
+
+    #[test]
+    fn test_progress() {
+        let shape = Shape {
+            rows: 8,
+            cols: 8,
+            bits: 8,
+        };
+        let values = values(shape.values() * 100_000);
+
+        // Chunks are 8193 matrices of 8 values, so the counts after the third one
+        let stop = |threads| {
+            let mut seen = Vec::new();
+            let counts =
+                rank_counts_with(shape, &mut values.iter().copied(), 100_000, threads, |c| {
+                    seen.push(c.iter().sum::<usize>());
+                    if seen.len() == 3 {
+                        ControlFlow::Break(())
+                    } else {
+                        ControlFlow::Continue(())
+                    }
+                });
+            assert_eq!(seen, [8193, 2 * 8193, 3 * 8193]);
+            counts
+        };
+        let expected = stop(1);
+        assert_eq!(expected.iter().sum::<usize>(), 3 * 8193);
+        for threads in [2, 4] {
+            assert_eq!(stop(threads), expected, "{} threads", threads);
+        }
+
+        let counts = rank_counts(shape, &mut values.iter().copied(), 3 * 8193, 1);
+        assert_eq!(counts, expected);
+    }
+}
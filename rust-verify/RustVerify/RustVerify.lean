@@ -1,3 +1,8 @@
 -- This module serves as the root of the `RustVerify` library.
 -- Import modules here that should be built as part of the library.
 import RustVerify.Basic
+import RustVerify.MatrixRank
+import RustVerify.Checked
+import RustVerify.RingBuffer
+import RustVerify.BitSet
+import RustVerify.SipHash
@@ -29,3 +29,53 @@ theorem Pcg64Si.next_u64_no_panic (self : sandbox.Pcg64Si) :
   ∃ res : U64 × sandbox.Pcg64Si, self.next_u64 = ok res := by
   unfold sandbox.Pcg64Si.next_u64
   progress*
+
+/-! Pure functional specifications of the generators of `sandbox`, on bit vectors, pinned
+to the same reference outputs as the Rust tests. -/
+
+def xorshift32 (x : BitVec 32) : BitVec 32 :=
+  let x := x ^^^ (x <<< 13)
+  let x := x ^^^ (x >>> 17)
+  x ^^^ (x <<< 5)
+
+def xorshift64 (x : BitVec 64) : BitVec 64 :=
+  let x := x ^^^ (x <<< 13)
+  let x := x ^^^ (x >>> 7)
+  x ^^^ (x <<< 17)
+
+/-- The next `(x, y, z, w)`, whose last word is the output. -/
+def xorshift128 (x y z w : BitVec 32) : BitVec 32 × BitVec 32 × BitVec 32 × BitVec 32 :=
+  let t := x ^^^ (x <<< 11)
+  (y, z, w, w ^^^ (w >>> 19) ^^^ t ^^^ (t >>> 8))
+
+def pcg32si_step (state : BitVec 32) : BitVec 32 :=
+  state * 747796405#32 + 2891336453#32
+
+def pcg32si_output (state : BitVec 32) : BitVec 32 :=
+  let word := ((state >>> ((state >>> 28) + 4#32)) ^^^ state) * 277803737#32
+  (word >>> 22) ^^^ word
+
+def pcg32si_new (seed : BitVec 32) : BitVec 32 :=
+  pcg32si_step (pcg32si_step seed + seed)
+
+example : xorshift32 2463534242#32 = 723471715#32 := by decide
+example : xorshift64 88172645463325252#64 = 8748534153485358512#64 := by decide
+example : (xorshift128 123456789#32 362436069#32 521288629#32 88675123#32).2.2.2 =
+    3701687786#32 := by decide
+example : pcg32si_output (pcg32si_new 0#32) = 582399676#32 := by decide
+example : pcg32si_output (pcg32si_step (pcg32si_new 42#32)) = 765726045#32 := by decide
+
+theorem Xorshift32.next_u32_no_panic (self : sandbox.Xorshift32) :
+  ∃ res : U32 × sandbox.Xorshift32, self.next_u32 = ok res := by
+  unfold sandbox.Xorshift32.next_u32
+  progress*
+
+theorem Xorshift64.next_u64_no_panic (self : sandbox.Xorshift64) :
+  ∃ res : U64 × sandbox.Xorshift64, self.next_u64 = ok res := by
+  unfold sandbox.Xorshift64.next_u64
+  progress*
+
+theorem Xorshift128.next_u32_no_panic (self : sandbox.Xorshift128) :
+  ∃ res : U32 × sandbox.Xorshift128, self.next_u32 = ok res := by
+  unfold sandbox.Xorshift128.next_u32
+  progress*
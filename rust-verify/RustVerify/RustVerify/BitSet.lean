@@ -0,0 +1,113 @@
+import Aeneas
+import RustVerify.Basic
+import RustVerify.MatrixRank
+
+open Aeneas.Std Result Error
+
+/-! `sandbox.BitSet64` never panics on elements below 64: `set`, `clear` and `test` act on
+one bit of `bits` each, and `count` is the number of bits set. -/
+
+namespace sandbox
+
+/-- The number of bits set in `x`. -/
+def popcount (x : BitVec 64) : Nat :=
+  (List.range 64).countP (x.getLsbD ·)
+
+example : popcount 0#64 = 0 := by decide
+example : popcount (-1#64) = 64 := by decide
+example : popcount 0x8000_0001_8000_0003#64 = 5 := by decide
+
+@[progress]
+theorem UScalar.or_spec {ty} (x y : UScalar ty) :
+  ∃ z, toResult (x ||| y) = ok z ∧ z.val = (x ||| y).val ∧ z.bv = x.bv ||| y.bv := by
+  simp [toResult]
+  rfl
+
+@[progress]
+theorem UScalar.not_spec {ty} (x : UScalar ty) :
+  ∃ z, toResult (~~~ x) = ok z ∧ z.bv = ~~~ x.bv := by
+  simp [toResult]
+  rfl
+
+theorem BitSet64.new_spec :
+    ∃ set, BitSet64.new = ok set ∧ set.bits.bv = 0#64 := by
+  simp [BitSet64.new]
+
+theorem BitSet64.set_spec (self : BitSet64) (i : U32) (hi : i.val < 64) :
+    ∃ self', self.set i = ok self' ∧ self'.bits.bv = self.bits.bv ||| 1#64 <<< i.val := by
+  unfold BitSet64.set
+  progress as ⟨i1, hi1⟩
+  progress as ⟨i2, hi2⟩
+  simp_all
+
+theorem BitSet64.clear_spec (self : BitSet64) (i : U32) (hi : i.val < 64) :
+    ∃ self', self.clear i = ok self' ∧
+      self'.bits.bv = self.bits.bv &&& ~~~(1#64 <<< i.val) := by
+  unfold BitSet64.clear
+  progress as ⟨i1, hi1⟩
+  progress as ⟨i2, hi2⟩
+  progress as ⟨i3, hi3⟩
+  simp_all
+
+theorem BitSet64.test_spec (self : BitSet64) (i : U32) (hi : i.val < 64) :
+    ∃ b, self.test i = ok b ∧ b = self.bits.bv.getLsbD i.val := by
+  unfold BitSet64.test
+  progress as ⟨i1, hi1⟩
+  progress as ⟨i2, hi2⟩
+  simp [UScalar.eq_equiv_bv_eq, hi2, hi1]
+  bv_decide
+
+/-- `set` adds `i` and keeps the other elements. -/
+theorem BitSet64.test_set (self : BitSet64) (i j : U32) (hi : i.val < 64) (hj : j.val < 64) :
+    ∃ self' b, self.set i = ok self' ∧ self'.test j = ok b ∧
+      b = (i.val = j.val || self.bits.bv.getLsbD j.val) := by
+  have ⟨self', hset, hbits⟩ := BitSet64.set_spec self i hi
+  have ⟨b, htest, hb⟩ := BitSet64.test_spec self' j hj
+  refine ⟨self', b, hset, htest, ?_⟩
+  simp [hb, hbits, BitVec.getLsbD_shiftLeft, hj]
+  by_cases h : i.val = j.val <;> simp [h, Bool.or_comm] <;> omega
+
+/-- `clear` removes `i` and keeps the other elements. -/
+theorem BitSet64.test_clear (self : BitSet64) (i j : U32) (hi : i.val < 64) (hj : j.val < 64) :
+    ∃ self' b, self.clear i = ok self' ∧ self'.test j = ok b ∧
+      b = (i.val != j.val && self.bits.bv.getLsbD j.val) := by
+  have ⟨self', hclear, hbits⟩ := BitSet64.clear_spec self i hi
+  have ⟨b, htest, hb⟩ := BitSet64.test_spec self' j hj
+  refine ⟨self', b, hclear, htest, ?_⟩
+  simp [hb, hbits, BitVec.getLsbD_shiftLeft, hj]
+  by_cases h : i.val = j.val <;> simp [h, Bool.and_comm] <;> omega
+
+/-- The loop adds the bits from `i` on to `count`. -/
+@[progress]
+theorem BitSet64.count_loop_spec (self : BitSet64) (count i : U32) (hi : i.val ≤ 64)
+    (hcount : count.val ≤ i.val) :
+    ∃ c, BitSet64.count_loop self count i = ok c ∧
+      c.val = count.val + (List.range' i.val (64 - i.val)).countP (self.bits.bv.getLsbD ·) ∧
+      c.val ≤ 64 := by
+  unfold BitSet64.count_loop
+  split
+  · progress as ⟨i1, hi1⟩
+    progress as ⟨i2, hi2⟩
+    have hbit : i2.val = (self.bits.bv.getLsbD i.val).toNat := by
+      simp [hi2, hi1, UScalar.val]
+      bv_decide
+    progress as ⟨i3, hi3⟩
+    progress as ⟨count1, hcount1⟩
+    progress as ⟨i4, hi4⟩
+    progress as ⟨c, hc, hc'⟩
+    refine ⟨hc ▸ ?_, hc'⟩
+    rw [show 64 - i.val = (64 - i4.val) + 1 by scalar_tac, List.range'_succ,
+      List.countP_cons]
+    cases h : self.bits.bv.getLsbD i.val <;> simp_all <;> omega
+  · simp
+    scalar_tac
+termination_by 64 - i.val
+decreasing_by scalar_decr_tac
+
+theorem BitSet64.count_spec (self : BitSet64) :
+    ∃ c, self.count = ok c ∧ c.val = popcount self.bits.bv ∧ c.val ≤ 64 := by
+  unfold BitSet64.count
+  progress as ⟨c, hc, hc'⟩
+  simp_all [popcount, List.range_eq_range']
+
+end sandbox
@@ -0,0 +1,72 @@
+import Aeneas
+import RustVerify.Basic
+
+open Aeneas.Std Result Error
+
+/-! Unlike `add` and `fma`, their checked, saturating and wrapping counterparts never fail,
+without any precondition, and compute the mathematical result: `some` of it exactly when it
+fits in an `I32`, clamped to the range of `I32`, or modulo `2^32`. -/
+
+namespace sandbox
+
+theorem narrow_spec (x : I64) :
+    ∃ r, narrow x = ok r ∧
+      (I32.min ≤ x.val ∧ x.val ≤ I32.max → ∃ c : I32, r = some c ∧ (c : ℤ) = x.val) ∧
+      (x.val < I32.min ∨ I32.max < x.val → r = none) := by
+  unfold narrow
+  progress*
+  all_goals simp_all [core.num.I32.MIN, core.num.I32.MAX]
+  all_goals scalar_tac
+
+theorem saturate_spec (x : I64) :
+    ∃ c, saturate x = ok c ∧ (c : ℤ) = max I32.min (min I32.max x.val) := by
+  unfold saturate
+  progress*
+  all_goals simp_all [core.num.I32.MIN, core.num.I32.MAX]
+  all_goals scalar_tac
+
+theorem checked_add_spec (a b : I32) :
+    ∃ r, checked_add a b = ok r ∧
+      (I32.min ≤ ↑a + ↑b ∧ ↑a + ↑b ≤ I32.max → ∃ c : I32, r = some c ∧ (c : ℤ) = ↑a + ↑b) ∧
+      (↑a + ↑b < I32.min ∨ I32.max < ↑a + ↑b → r = none) := by
+  unfold checked_add
+  progress*
+  have ⟨r, hr, hsome, hnone⟩ := narrow_spec i2
+  simp_all
+
+theorem checked_fma_spec (a b c : I32) :
+    ∃ r, checked_fma a b c = ok r ∧
+      (I32.min ≤ ↑a * ↑b + ↑c ∧ ↑a * ↑b + ↑c ≤ I32.max →
+        ∃ d : I32, r = some d ∧ (d : ℤ) = ↑a * ↑b + ↑c) ∧
+      (↑a * ↑b + ↑c < I32.min ∨ I32.max < ↑a * ↑b + ↑c → r = none) := by
+  unfold checked_fma
+  progress*
+  have ⟨r, hr, hsome, hnone⟩ := narrow_spec i4
+  simp_all
+
+theorem saturating_add_spec (a b : I32) :
+    ∃ c, saturating_add a b = ok c ∧ (c : ℤ) = max I32.min (min I32.max (↑a + ↑b)) := by
+  unfold saturating_add
+  progress*
+  have ⟨c, hc, hval⟩ := saturate_spec i2
+  simp_all
+
+theorem saturating_fma_spec (a b c : I32) :
+    ∃ d, saturating_fma a b c = ok d ∧
+      (d : ℤ) = max I32.min (min I32.max (↑a * ↑b + ↑c)) := by
+  unfold saturating_fma
+  progress*
+  have ⟨d, hd, hval⟩ := saturate_spec i4
+  simp_all
+
+theorem wrapping_add_spec (a b : I32) :
+    ∃ c, wrapping_add a b = ok c ∧ c.bv = a.bv + b.bv := by
+  unfold wrapping_add
+  simp [core.num.I32.wrapping_add_bv_eq]
+
+theorem wrapping_fma_spec (a b c : I32) :
+    ∃ d, wrapping_fma a b c = ok d ∧ d.bv = a.bv * b.bv + c.bv := by
+  unfold wrapping_fma
+  simp [core.num.I32.wrapping_mul_bv_eq, core.num.I32.wrapping_add_bv_eq, toResult]
+
+end sandbox
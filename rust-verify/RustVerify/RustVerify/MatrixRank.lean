@@ -0,0 +1,104 @@
+import Aeneas
+import RustVerify.Basic
+
+open Aeneas.Std Result Error
+
+/-! `sandbox.matrix_rank` never panics and returns at most `min(rows, 32)`, by a spec of
+each of its loops. -/
+
+namespace sandbox
+
+@[progress]
+theorem UScalar.and_spec {ty} (x y : UScalar ty) :
+  ∃ z, toResult (x &&& y) = ok z ∧ z.val = (x &&& y).val ∧ z.bv = x.bv &&& y.bv := by
+  simp [toResult]
+  rfl
+
+/-- The pivot search stops at a row from `pivot` on, or at `n` if there is none. -/
+@[progress]
+theorem matrix_rank_loop0_spec (matrix : Slice U32) (n : Usize) (bit : U32) (pivot : Usize)
+    (hn : n.val = matrix.length) (hbit : bit.val < 32) (hpivot : pivot.val ≤ n.val) :
+    ∃ p, matrix_rank_loop0 matrix n bit pivot = ok p ∧ pivot.val ≤ p.val ∧ p.val ≤ n.val := by
+  unfold matrix_rank_loop0
+  split
+  · progress as ⟨i, hi⟩
+    progress as ⟨i1, hi1⟩
+    progress as ⟨i2, hi2⟩
+    split
+    · progress as ⟨pivot1, hpivot1⟩
+      progress as ⟨p, hp, hp'⟩
+      scalar_tac
+    · simp
+      scalar_tac
+  · simp
+    scalar_tac
+termination_by n.val - pivot.val
+decreasing_by scalar_decr_tac
+
+/-- The elimination below and above the pivot keeps the number of rows. -/
+@[progress]
+theorem matrix_rank_loop1_spec (matrix : Slice U32) (n rank : Usize) (bit row : U32)
+    (i : Usize) (hn : n.val = matrix.length) (hbit : bit.val < 32) (hi : i.val ≤ n.val) :
+    ∃ matrix', matrix_rank_loop1 matrix n rank bit row i = ok matrix' ∧
+      matrix'.length = matrix.length := by
+  unfold matrix_rank_loop1
+  split
+  · split
+    · progress as ⟨i1, hi1⟩
+      progress as ⟨i2, hi2⟩
+      progress as ⟨i3, hi3⟩
+      split
+      · progress as ⟨i4, hi4⟩
+        progress as ⟨i5, hi5⟩
+        progress as ⟨matrix1, hmatrix1⟩
+        progress as ⟨i6, hi6⟩
+        progress as ⟨matrix', hmatrix'⟩
+        simp_all
+      · progress as ⟨i4, hi4⟩
+        progress as ⟨matrix', hmatrix'⟩
+        simp_all
+    · progress as ⟨i1, hi1⟩
+      progress as ⟨matrix', hmatrix'⟩
+      simp_all
+  · simp
+termination_by n.val - i.val
+decreasing_by all_goals scalar_decr_tac
+
+/-- Each of the 32 bits adds at most one to the rank, and only if a row from `rank` on
+has it set. -/
+@[progress]
+theorem matrix_rank_loop2_spec (matrix : Slice U32) (n rank : Usize) (bit : U32)
+    (hn : n.val = matrix.length) (hbit : bit.val ≤ 32) (hrank : rank.val ≤ bit.val)
+    (hrank' : rank.val ≤ n.val) :
+    ∃ r matrix', matrix_rank_loop2 matrix n rank bit = ok (r, matrix') ∧
+      r.val ≤ 32 ∧ r.val ≤ n.val ∧ matrix'.length = matrix.length := by
+  unfold matrix_rank_loop2
+  split
+  · progress as ⟨pivot, hpivot, hpivot'⟩
+    split
+    · progress as ⟨row, hrow⟩
+      progress as ⟨x, hx⟩
+      progress as ⟨matrix1, hmatrix1⟩
+      progress as ⟨matrix2, hmatrix2⟩
+      progress as ⟨matrix3, hmatrix3⟩
+      progress as ⟨rank1, hrank1⟩
+      progress as ⟨bit1, hbit1⟩
+      progress as ⟨r, matrix', hr, hr', hmatrix'⟩
+      simp_all
+    · progress as ⟨bit1, hbit1⟩
+      progress as ⟨r, matrix', hr, hr', hmatrix'⟩
+      simp_all
+  · simp
+    scalar_tac
+termination_by 32 - bit.val
+decreasing_by all_goals scalar_decr_tac
+
+theorem matrix_rank_spec (matrix : Slice U32) :
+    ∃ rank matrix', matrix_rank matrix = ok (rank, matrix') ∧
+      rank.val ≤ min matrix.length 32 ∧ matrix'.length = matrix.length := by
+  unfold matrix_rank
+  progress as ⟨rank, matrix', hrank, hrank', hmatrix'⟩
+  simp_all
+  omega
+
+end sandbox
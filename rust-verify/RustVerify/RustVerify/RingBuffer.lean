@@ -0,0 +1,92 @@
+import Aeneas
+import RustVerify.Basic
+
+open Aeneas.Std Result Error
+
+/-! `sandbox.RingBuffer` is a queue of at most 16 values: under `RingBuffer.inv`, which
+`new` establishes and `push` and `pop` keep, neither of them panics and both act on
+`RingBuffer.toList` as on a list. -/
+
+namespace sandbox
+
+/-- The bounds under which `push` and `pop` index within `buf`. -/
+def RingBuffer.inv (self : RingBuffer) : Prop :=
+  self.head.val < 16 ∧ self.size.val ≤ 16
+
+/-- The values from the front to the back. -/
+def RingBuffer.toList (self : RingBuffer) : List U32 :=
+  (List.range self.size.val).map fun k => self.buf.val[(self.head.val + k) % 16]!
+
+@[simp]
+theorem RingBuffer.CAPACITY_val : RingBuffer.CAPACITY.val = 16 := by
+  simp [global_simps]
+
+@[simp]
+theorem RingBuffer.toList_length (self : RingBuffer) : self.toList.length = self.size.val := by
+  simp [RingBuffer.toList]
+
+theorem RingBuffer.new_spec :
+    ∃ ring, RingBuffer.new = ok ring ∧ ring.inv ∧ ring.toList = [] := by
+  simp [RingBuffer.new, RingBuffer.inv, RingBuffer.toList]
+
+theorem RingBuffer.len_spec (self : RingBuffer) :
+    ∃ n, self.len = ok n ∧ n.val = self.toList.length := by
+  simp [RingBuffer.len]
+
+theorem RingBuffer.is_empty_spec (self : RingBuffer) :
+    ∃ b, self.is_empty = ok b ∧ (b ↔ self.toList = []) := by
+  simp [RingBuffer.is_empty, ← List.length_eq_zero_iff, Usize.eq_iff]
+
+/-- A full buffer is left as it is, and otherwise `value` goes at the back. -/
+@[progress]
+theorem RingBuffer.push_spec (self : RingBuffer) (value : U32) (h : self.inv) :
+    ∃ pushed self', self.push value = ok (pushed, self') ∧ self'.inv ∧
+      (pushed ↔ self.toList.length < 16) ∧
+      self'.toList = if pushed then self.toList ++ [value] else self.toList := by
+  unfold RingBuffer.push RingBuffer.inv at *
+  split
+  · simp_all
+  · progress as ⟨i, hi⟩
+    progress as ⟨i1, hi1⟩
+    progress as ⟨a, ha⟩
+    progress as ⟨i2, hi2⟩
+    have hsize : self.size.val < 16 := by scalar_tac
+    refine ⟨_, _, rfl, by simp; scalar_tac, by simp [hsize], ?_⟩
+    simp only [RingBuffer.toList, if_true, ha, hi2, List.range_succ, List.map_append,
+      List.map_singleton]
+    congr 1
+    · apply List.map_congr_left
+      intro k hk
+      simp at hk
+      rw [List.getElem!_set_ne]
+      simp_all
+      omega
+    · simp_all [List.getElem!_set_eq]
+      scalar_tac
+
+/-- An empty buffer is left as it is, and otherwise its front value is removed. -/
+@[progress]
+theorem RingBuffer.pop_spec (self : RingBuffer) (h : self.inv) :
+    ∃ front self', self.pop = ok (front, self') ∧ self'.inv ∧
+      front = self.toList.head? ∧ self'.toList = self.toList.tail := by
+  unfold RingBuffer.pop RingBuffer.inv at *
+  split
+  · simp_all [RingBuffer.toList]
+  · progress as ⟨value, hvalue⟩
+    progress as ⟨i, hi⟩
+    progress as ⟨i1, hi1⟩
+    progress as ⟨i2, hi2⟩
+    have hsize : 0 < self.size.val := by scalar_tac
+    refine ⟨_, _, rfl, by simp_all; scalar_tac, ?_, ?_⟩
+    · rw [RingBuffer.toList, List.range_eq_range', List.range'_eq_cons_iff.mpr ⟨hsize, rfl⟩]
+      simp_all
+    · simp only [RingBuffer.toList, ← List.map_tail, List.tail_range]
+      rw [show i2.val = self.size.val - 1 by scalar_tac, List.range'_eq_map_range,
+        List.map_map]
+      apply List.map_congr_left
+      intro k hk
+      simp_all
+      congr 2
+      omega
+
+end sandbox
@@ -25,13 +25,98 @@ def fma (a : I32) (b : I32) (c : I32) : Result I32 :=
   let i ← a * b
   i + c
 
+/- Written by hand, not by Aeneas: sandbox.llbc predates the items from here to
+   `wrapping_fma`. `make` replaces them with the translation of the current src/main.rs. -/
+
+/- [sandbox::narrow]:
+   Source: 'src/main.rs', lines 14:0-20:1 -/
+def narrow (x : I64) : Result (Option I32) :=
+  do
+  let i ← (↑(IScalar.cast .I64 core.num.I32.MIN) : Result I64)
+  if i <= x
+  then
+    do
+    let i1 ← (↑(IScalar.cast .I64 core.num.I32.MAX) : Result I64)
+    if x <= i1
+    then do
+         let i2 ← (↑(IScalar.cast .I32 x) : Result I32)
+         ok (some i2)
+    else ok none
+  else ok none
+
+/- [sandbox::saturate]:
+   Source: 'src/main.rs', lines 23:0-31:1 -/
+def saturate (x : I64) : Result I32 :=
+  do
+  let i ← (↑(IScalar.cast .I64 core.num.I32.MIN) : Result I64)
+  if x < i
+  then ok core.num.I32.MIN
+  else
+    do
+    let i1 ← (↑(IScalar.cast .I64 core.num.I32.MAX) : Result I64)
+    if x > i1
+    then ok core.num.I32.MAX
+    else (↑(IScalar.cast .I32 x) : Result I32)
+
+/- [sandbox::checked_add]:
+   Source: 'src/main.rs', lines 34:0-36:1 -/
+def checked_add (a : I32) (b : I32) : Result (Option I32) :=
+  do
+  let i ← (↑(IScalar.cast .I64 a) : Result I64)
+  let i1 ← (↑(IScalar.cast .I64 b) : Result I64)
+  let i2 ← i + i1
+  narrow i2
+
+/- [sandbox::checked_fma]:
+   Source: 'src/main.rs', lines 40:0-42:1 -/
+def checked_fma (a : I32) (b : I32) (c : I32) : Result (Option I32) :=
+  do
+  let i ← (↑(IScalar.cast .I64 a) : Result I64)
+  let i1 ← (↑(IScalar.cast .I64 b) : Result I64)
+  let i2 ← i * i1
+  let i3 ← (↑(IScalar.cast .I64 c) : Result I64)
+  let i4 ← i2 + i3
+  narrow i4
+
+/- [sandbox::saturating_add]:
+   Source: 'src/main.rs', lines 44:0-46:1 -/
+def saturating_add (a : I32) (b : I32) : Result I32 :=
+  do
+  let i ← (↑(IScalar.cast .I64 a) : Result I64)
+  let i1 ← (↑(IScalar.cast .I64 b) : Result I64)
+  let i2 ← i + i1
+  saturate i2
+
+/- [sandbox::saturating_fma]:
+   Source: 'src/main.rs', lines 50:0-52:1 -/
+def saturating_fma (a : I32) (b : I32) (c : I32) : Result I32 :=
+  do
+  let i ← (↑(IScalar.cast .I64 a) : Result I64)
+  let i1 ← (↑(IScalar.cast .I64 b) : Result I64)
+  let i2 ← i * i1
+  let i3 ← (↑(IScalar.cast .I64 c) : Result I64)
+  let i4 ← i2 + i3
+  saturate i4
+
+/- [sandbox::wrapping_add]:
+   Source: 'src/main.rs', lines 54:0-56:1 -/
+def wrapping_add (a : I32) (b : I32) : Result I32 :=
+  ok (core.num.I32.wrapping_add a b)
+
+/- [sandbox::wrapping_fma]:
+   Source: 'src/main.rs', lines 58:0-60:1 -/
+def wrapping_fma (a : I32) (b : I32) (c : I32) : Result I32 :=
+  do
+  let i ← (↑(core.num.I32.wrapping_mul a b) : Result I32)
+  ok (core.num.I32.wrapping_add i c)
+
 /- [sandbox::Pcg64Si]
-   Source: 'src/main.rs', lines 12:0-14:1 -/
+   Source: 'src/main.rs', lines 63:0-65:1 -/
 structure Pcg64Si where
   state : U64
 
 /- [sandbox::{sandbox::Pcg64Si}::next_u64]:
-   Source: 'src/main.rs', lines 17:4-27:5 -/
+   Source: 'src/main.rs', lines 68:4-78:5 -/
 def Pcg64Si.next_u64 (self : Pcg64Si) : Result (U64 × Pcg64Si) :=
   do
   let i ←
@@ -49,4 +134,459 @@ def Pcg64Si.next_u64 (self : Pcg64Si) : Result (U64 × Pcg64Si) :=
   let i7 ← (↑(i6 ^^^ word) : Result U64)
   ok (i7, { state := i1 })
 
+/- Written by hand, not by Aeneas: sandbox.llbc predates the items from here to
+   `Xorshift128.next_u32`. `make` replaces them with the translation of the current src/main.rs. -/
+
+/- [sandbox::PCG32si]
+   Source: 'src/main.rs', lines 83:0-85:1 -/
+structure PCG32si where
+  state : U32
+
+/- [sandbox::{sandbox::PCG32si}::PCG_DEFAULT_MULTIPLIER_32]
+   Source: 'src/main.rs', lines 88:4-88:53 -/
+@[global_simps]
+def PCG32si.PCG_DEFAULT_MULTIPLIER_32_body : Result U32 := ok 747796405#u32
+@[global_simps, irreducible]
+def PCG32si.PCG_DEFAULT_MULTIPLIER_32 : U32 :=
+  eval_global PCG32si.PCG_DEFAULT_MULTIPLIER_32_body
+
+/- [sandbox::{sandbox::PCG32si}::PCG_DEFAULT_INCREMENT_32]
+   Source: 'src/main.rs', lines 89:4-89:53 -/
+@[global_simps]
+def PCG32si.PCG_DEFAULT_INCREMENT_32_body : Result U32 := ok 2891336453#u32
+@[global_simps, irreducible]
+def PCG32si.PCG_DEFAULT_INCREMENT_32 : U32 :=
+  eval_global PCG32si.PCG_DEFAULT_INCREMENT_32_body
+
+/- [sandbox::{sandbox::PCG32si}::pcg_oneseq_32_step_r]:
+   Source: 'src/main.rs', lines 91:4-96:5 -/
+def PCG32si.pcg_oneseq_32_step_r (self : PCG32si) : Result PCG32si :=
+  do
+  let i ←
+    (↑(core.num.U32.wrapping_mul self.state PCG32si.PCG_DEFAULT_MULTIPLIER_32)
+      : Result U32)
+  let i1 ←
+    (↑(core.num.U32.wrapping_add i PCG32si.PCG_DEFAULT_INCREMENT_32) : Result
+      U32)
+  ok { state := i1 }
+
+/- [sandbox::{sandbox::PCG32si}::pcg_output_rxs_m_xs_32_32]:
+   Source: 'src/main.rs', lines 98:4-101:5 -/
+def PCG32si.pcg_output_rxs_m_xs_32_32 (state : U32) : Result U32 :=
+  do
+  let i ← state >>> 28#i32
+  let i1 ← (↑(core.num.U32.wrapping_add i 4#u32) : Result U32)
+  let i2 ← state >>> i1
+  let i3 ← (↑(i2 ^^^ state) : Result U32)
+  let word ← (↑(core.num.U32.wrapping_mul i3 277803737#u32) : Result U32)
+  let i4 ← word >>> 22#i32
+  (↑(i4 ^^^ word) : Result U32)
+
+/- [sandbox::{sandbox::PCG32si}::new]:
+   Source: 'src/main.rs', lines 103:4-109:5 -/
+def PCG32si.new (seed : U32) : Result PCG32si :=
+  do
+  let rng ← PCG32si.pcg_oneseq_32_step_r { state := seed }
+  let i ← (↑(core.num.U32.wrapping_add rng.state seed) : Result U32)
+  PCG32si.pcg_oneseq_32_step_r { state := i }
+
+/- [sandbox::{sandbox::PCG32si}::next_u32]:
+   Source: 'src/main.rs', lines 111:4-115:5 -/
+def PCG32si.next_u32 (self : PCG32si) : Result (U32 × PCG32si) :=
+  do
+  let self1 ← PCG32si.pcg_oneseq_32_step_r self
+  let i ← PCG32si.pcg_output_rxs_m_xs_32_32 self.state
+  ok (i, self1)
+
+/- [sandbox::Xorshift32]
+   Source: 'src/main.rs', lines 119:0-121:1 -/
+structure Xorshift32 where
+  state : U32
+
+/- [sandbox::{sandbox::Xorshift32}::next_u32]:
+   Source: 'src/main.rs', lines 124:4-131:5 -/
+def Xorshift32.next_u32 (self : Xorshift32) : Result (U32 × Xorshift32) :=
+  do
+  let i ← self.state <<< 13#i32
+  let x ← (↑(self.state ^^^ i) : Result U32)
+  let i1 ← x >>> 17#i32
+  let x1 ← (↑(x ^^^ i1) : Result U32)
+  let i2 ← x1 <<< 5#i32
+  let x2 ← (↑(x1 ^^^ i2) : Result U32)
+  ok (x2, { state := x2 })
+
+/- [sandbox::Xorshift64]
+   Source: 'src/main.rs', lines 135:0-137:1 -/
+structure Xorshift64 where
+  state : U64
+
+/- [sandbox::{sandbox::Xorshift64}::next_u64]:
+   Source: 'src/main.rs', lines 140:4-147:5 -/
+def Xorshift64.next_u64 (self : Xorshift64) : Result (U64 × Xorshift64) :=
+  do
+  let i ← self.state <<< 13#i32
+  let x ← (↑(self.state ^^^ i) : Result U64)
+  let i1 ← x >>> 7#i32
+  let x1 ← (↑(x ^^^ i1) : Result U64)
+  let i2 ← x1 <<< 17#i32
+  let x2 ← (↑(x1 ^^^ i2) : Result U64)
+  ok (x2, { state := x2 })
+
+/- [sandbox::Xorshift128]
+   Source: 'src/main.rs', lines 150:0-155:1 -/
+structure Xorshift128 where
+  x : U32
+  y : U32
+  z : U32
+  w : U32
+
+/- [sandbox::{sandbox::Xorshift128}::next_u32]:
+   Source: 'src/main.rs', lines 158:4-165:5 -/
+def Xorshift128.next_u32 (self : Xorshift128) : Result (U32 × Xorshift128) :=
+  do
+  let i ← self.x <<< 11#i32
+  let t ← (↑(self.x ^^^ i) : Result U32)
+  let i1 ← self.w >>> 19#i32
+  let i2 ← (↑(self.w ^^^ i1) : Result U32)
+  let i3 ← (↑(i2 ^^^ t) : Result U32)
+  let i4 ← t >>> 8#i32
+  let i5 ← (↑(i3 ^^^ i4) : Result U32)
+  ok (i5, { x := self.y, y := self.z, z := self.w, w := i5 })
+
+/- Written by hand, not by Aeneas: sandbox.llbc predates the items from here to
+   `matrix_rank`. `make` replaces them with the translation of the current src/main.rs. -/
+
+/- [sandbox::matrix_rank]: loop 0:
+   Source: 'src/main.rs', lines 177:8-179:9 -/
+def matrix_rank_loop0
+  (matrix : Slice U32) (n : Usize) (bit : U32) (pivot : Usize) : Result Usize :=
+  if pivot < n
+  then
+    do
+    let i ← Slice.index_usize matrix pivot
+    let i1 ← i >>> bit
+    let i2 ← (↑(i1 &&& 1#u32) : Result U32)
+    if i2 = 0#u32
+    then do
+         let pivot1 ← pivot + 1#usize
+         matrix_rank_loop0 matrix n bit pivot1
+    else ok pivot
+  else ok pivot
+partial_fixpoint
+
+/- [sandbox::matrix_rank]: loop 1:
+   Source: 'src/main.rs', lines 185:12-190:13 -/
+def matrix_rank_loop1
+  (matrix : Slice U32) (n : Usize) (rank : Usize) (bit : U32) (row : U32)
+  (i : Usize) :
+  Result (Slice U32)
+  :=
+  if i < n
+  then
+    if i != rank
+    then
+      do
+      let i1 ← Slice.index_usize matrix i
+      let i2 ← i1 >>> bit
+      let i3 ← (↑(i2 &&& 1#u32) : Result U32)
+      if i3 = 1#u32
+      then
+        do
+        let i4 ← Slice.index_usize matrix i
+        let i5 ← (↑(i4 ^^^ row) : Result U32)
+        let matrix1 ← Slice.update matrix i i5
+        let i6 ← i + 1#usize
+        matrix_rank_loop1 matrix1 n rank bit row i6
+      else do
+           let i4 ← i + 1#usize
+           matrix_rank_loop1 matrix n rank bit row i4
+    else do
+         let i1 ← i + 1#usize
+         matrix_rank_loop1 matrix n rank bit row i1
+  else ok matrix
+partial_fixpoint
+
+/- [sandbox::matrix_rank]: loop 2:
+   Source: 'src/main.rs', lines 175:4-194:5 -/
+def matrix_rank_loop2
+  (matrix : Slice U32) (n : Usize) (rank : Usize) (bit : U32) :
+  Result (Usize × (Slice U32))
+  :=
+  if bit < 32#u32
+  then
+    do
+    let pivot ← matrix_rank_loop0 matrix n bit rank
+    if pivot < n
+    then
+      do
+      let row ← Slice.index_usize matrix pivot
+      let i ← Slice.index_usize matrix rank
+      let matrix1 ← Slice.update matrix pivot i
+      let matrix2 ← Slice.update matrix1 rank row
+      let matrix3 ← matrix_rank_loop1 matrix2 n rank bit row 0#usize
+      let rank1 ← rank + 1#usize
+      let bit1 ← bit + 1#u32
+      matrix_rank_loop2 matrix3 n rank1 bit1
+    else do
+         let bit1 ← bit + 1#u32
+         matrix_rank_loop2 matrix n rank bit1
+  else ok (rank, matrix)
+partial_fixpoint
+
+/- [sandbox::matrix_rank]:
+   Source: 'src/main.rs', lines 171:0-196:1 -/
+def matrix_rank (matrix : Slice U32) : Result (Usize × (Slice U32)) :=
+  let n := Slice.len matrix
+  matrix_rank_loop2 matrix n 0#usize 0#u32
+
+/- Written by hand, not by Aeneas: sandbox.llbc predates the items from here to
+   `BitSet64.count`. `make` replaces them with the translation of the current src/main.rs. -/
+
+/- [sandbox::RingBuffer]
+   Source: 'src/main.rs', lines 200:0-204:1 -/
+structure RingBuffer where
+  buf : Array U32 16#usize
+  head : Usize
+  size : Usize
+
+/- [sandbox::{sandbox::RingBuffer}::CAPACITY]
+   Source: 'src/main.rs', lines 209:4-209:31 -/
+@[global_simps]
+def RingBuffer.CAPACITY_body : Result Usize := ok 16#usize
+@[global_simps, irreducible]
+def RingBuffer.CAPACITY : Usize := eval_global RingBuffer.CAPACITY_body
+
+/- [sandbox::{sandbox::RingBuffer}::new]:
+   Source: 'src/main.rs', lines 211:4-217:5 -/
+def RingBuffer.new : Result RingBuffer :=
+  ok
+  {
+    buf := Array.repeat 16#usize 0#u32,
+    head := 0#usize,
+    size := 0#usize
+  }
+
+/- [sandbox::{sandbox::RingBuffer}::len]:
+   Source: 'src/main.rs', lines 219:4-221:5 -/
+def RingBuffer.len (self : RingBuffer) : Result Usize :=
+  ok self.size
+
+/- [sandbox::{sandbox::RingBuffer}::is_empty]:
+   Source: 'src/main.rs', lines 223:4-225:5 -/
+def RingBuffer.is_empty (self : RingBuffer) : Result Bool :=
+  ok (self.size = 0#usize)
+
+/- [sandbox::{sandbox::RingBuffer}::push]:
+   Source: 'src/main.rs', lines 228:4-236:5 -/
+def RingBuffer.push
+  (self : RingBuffer) (value : U32) : Result (Bool × RingBuffer) :=
+  if self.size = RingBuffer.CAPACITY
+  then ok (false, self)
+  else
+    do
+    let i ← self.head + self.size
+    let i1 ← i % RingBuffer.CAPACITY
+    let a ← Array.update self.buf i1 value
+    let i2 ← self.size + 1#usize
+    ok (true, { self with buf := a, size := i2 })
+
+/- [sandbox::{sandbox::RingBuffer}::pop]:
+   Source: 'src/main.rs', lines 239:4-248:5 -/
+def RingBuffer.pop (self : RingBuffer) : Result ((Option U32) × RingBuffer) :=
+  if self.size = 0#usize
+  then ok (none, self)
+  else
+    do
+    let value ← Array.index_usize self.buf self.head
+    let i ← self.head + 1#usize
+    let i1 ← i % RingBuffer.CAPACITY
+    let i2 ← self.size - 1#usize
+    ok (some value, { self with head := i1, size := i2 })
+
+/- [sandbox::BitSet64]
+   Source: 'src/main.rs', lines 253:0-255:1 -/
+structure BitSet64 where
+  bits : U64
+
+/- [sandbox::{sandbox::BitSet64}::new]:
+   Source: 'src/main.rs', lines 260:4-262:5 -/
+def BitSet64.new : Result BitSet64 :=
+  ok { bits := 0#u64 }
+
+/- [sandbox::{sandbox::BitSet64}::set]:
+   Source: 'src/main.rs', lines 265:4-267:5 -/
+def BitSet64.set (self : BitSet64) (i : U32) : Result BitSet64 :=
+  do
+  let i1 ← 1#u64 <<< i
+  let i2 ← (↑(self.bits ||| i1) : Result U64)
+  ok { bits := i2 }
+
+/- [sandbox::{sandbox::BitSet64}::clear]:
+   Source: 'src/main.rs', lines 270:4-272:5 -/
+def BitSet64.clear (self : BitSet64) (i : U32) : Result BitSet64 :=
+  do
+  let i1 ← 1#u64 <<< i
+  let i2 ← (↑(~~~ i1) : Result U64)
+  let i3 ← (↑(self.bits &&& i2) : Result U64)
+  ok { bits := i3 }
+
+/- [sandbox::{sandbox::BitSet64}::test]:
+   Source: 'src/main.rs', lines 275:4-277:5 -/
+def BitSet64.test (self : BitSet64) (i : U32) : Result Bool :=
+  do
+  let i1 ← self.bits >>> i
+  let i2 ← (↑(i1 &&& 1#u64) : Result U64)
+  ok (i2 = 1#u64)
+
+/- [sandbox::{sandbox::BitSet64}::count]: loop 0:
+   Source: 'src/main.rs', lines 283:8-286:9 -/
+def BitSet64.count_loop (self : BitSet64) (count : U32) (i : U32) : Result U32 :=
+  if i < 64#u32
+  then
+    do
+    let i1 ← self.bits >>> i
+    let i2 ← (↑(i1 &&& 1#u64) : Result U64)
+    let i3 ← (↑(UScalar.cast .U32 i2) : Result U32)
+    let count1 ← count + i3
+    let i4 ← i + 1#u32
+    BitSet64.count_loop self count1 i4
+  else ok count
+partial_fixpoint
+
+/- [sandbox::{sandbox::BitSet64}::count]:
+   Source: 'src/main.rs', lines 280:4-288:5 -/
+def BitSet64.count (self : BitSet64) : Result U32 :=
+  BitSet64.count_loop self 0#u32 0#u32
+
+/- Written by hand, not by Aeneas: sandbox.llbc predates the items from here to
+   `siphash13`. `make` replaces them with the translation of the current src/main.rs. -/
+
+/- [sandbox::rotl]:
+   Source: 'src/main.rs', lines 295:0-297:1 -/
+def rotl (x : U64) (b : U32) : Result U64 :=
+  do
+  let i ← x <<< b
+  let i1 ← 64#u32 - b
+  let i2 ← x >>> i1
+  (↑(i ||| i2) : Result U64)
+
+/- [sandbox::SipHash13]
+   Source: 'src/main.rs', lines 300:0-305:1 -/
+structure SipHash13 where
+  v0 : U64
+  v1 : U64
+  v2 : U64
+  v3 : U64
+
+/- [sandbox::{sandbox::SipHash13}::new]:
+   Source: 'src/main.rs', lines 308:4-315:5 -/
+def SipHash13.new (k0 : U64) (k1 : U64) : Result SipHash13 :=
+  do
+  let i ← (↑(k0 ^^^ 8317987319222330741#u64) : Result U64)
+  let i1 ← (↑(k1 ^^^ 7237128888997146477#u64) : Result U64)
+  let i2 ← (↑(k0 ^^^ 7816392313619706465#u64) : Result U64)
+  let i3 ← (↑(k1 ^^^ 8387220255154660723#u64) : Result U64)
+  ok { v0 := i, v1 := i1, v2 := i2, v3 := i3 }
+
+/- [sandbox::{sandbox::SipHash13}::round]:
+   Source: 'src/main.rs', lines 317:4-332:5 -/
+def SipHash13.round (self : SipHash13) : Result SipHash13 :=
+  do
+  let i ← (↑(core.num.U64.wrapping_add self.v0 self.v1) : Result U64)
+  let i1 ← rotl self.v1 13#u32
+  let i2 ← (↑(i1 ^^^ i) : Result U64)
+  let i3 ← rotl i 32#u32
+  let i4 ← (↑(core.num.U64.wrapping_add self.v2 self.v3) : Result U64)
+  let i5 ← rotl self.v3 16#u32
+  let i6 ← (↑(i5 ^^^ i4) : Result U64)
+  let i7 ← (↑(core.num.U64.wrapping_add i3 i6) : Result U64)
+  let i8 ← rotl i6 21#u32
+  let i9 ← (↑(i8 ^^^ i7) : Result U64)
+  let i10 ← (↑(core.num.U64.wrapping_add i4 i2) : Result U64)
+  let i11 ← rotl i2 17#u32
+  let i12 ← (↑(i11 ^^^ i10) : Result U64)
+  let i13 ← rotl i10 32#u32
+  ok { v0 := i7, v1 := i12, v2 := i13, v3 := i9 }
+
+/- [sandbox::{sandbox::SipHash13}::compress]:
+   Source: 'src/main.rs', lines 334:4-338:5 -/
+def SipHash13.compress (self : SipHash13) (m : U64) : Result SipHash13 :=
+  do
+  let i ← (↑(self.v3 ^^^ m) : Result U64)
+  let self1 ← SipHash13.round { self with v3 := i }
+  let i1 ← (↑(self1.v0 ^^^ m) : Result U64)
+  ok { self1 with v0 := i1 }
+
+/- [sandbox::{sandbox::SipHash13}::finish]:
+   Source: 'src/main.rs', lines 340:4-346:5 -/
+def SipHash13.finish (self : SipHash13) : Result (U64 × SipHash13) :=
+  do
+  let i ← (↑(self.v2 ^^^ 255#u64) : Result U64)
+  let self1 ← SipHash13.round { self with v2 := i }
+  let self2 ← SipHash13.round self1
+  let self3 ← SipHash13.round self2
+  let i1 ← (↑(self3.v0 ^^^ self3.v1) : Result U64)
+  let i2 ← (↑(i1 ^^^ self3.v2) : Result U64)
+  let i3 ← (↑(i2 ^^^ self3.v3) : Result U64)
+  ok (i3, self3)
+
+/- [sandbox::read_le]: loop 0:
+   Source: 'src/main.rs', lines 353:4-356:5 -/
+def read_le_loop
+  (msg : Slice U8) (start : Usize) (n : Usize) (word : U64) (j : Usize) :
+  Result U64
+  :=
+  if j < n
+  then
+    do
+    let i ← start + j
+    let i1 ← Slice.index_usize msg i
+    let i2 ← (↑(UScalar.cast .U64 i1) : Result U64)
+    let i3 ← 8#usize * j
+    let i4 ← i2 <<< i3
+    let word1 ← (↑(word ||| i4) : Result U64)
+    let j1 ← j + 1#usize
+    read_le_loop msg start n word1 j1
+  else ok word
+partial_fixpoint
+
+/- [sandbox::read_le]:
+   Source: 'src/main.rs', lines 350:0-358:1 -/
+def read_le (msg : Slice U8) (start : Usize) (n : Usize) : Result U64 :=
+  read_le_loop msg start n 0#u64 0#usize
+
+/- [sandbox::siphash13]: loop 0:
+   Source: 'src/main.rs', lines 366:4-369:5 -/
+def siphash13_loop
+  (msg : Slice U8) (len : Usize) (state : SipHash13) (i : Usize) :
+  Result (SipHash13 × Usize)
+  :=
+  do
+  let i1 ← len - i
+  if i1 >= 8#usize
+  then
+    do
+    let i2 ← read_le msg i 8#usize
+    let state1 ← SipHash13.compress state i2
+    let i3 ← i + 8#usize
+    siphash13_loop msg len state1 i3
+  else ok (state, i)
+partial_fixpoint
+
+/- [sandbox::siphash13]:
+   Source: 'src/main.rs', lines 361:0-372:1 -/
+def siphash13 (k0 : U64) (k1 : U64) (msg : Slice U8) : Result U64 :=
+  do
+  let len := Slice.len msg
+  let state ← SipHash13.new k0 k1
+  let (state1, i) ← siphash13_loop msg len state 0#usize
+  let i1 ← len - i
+  let i2 ← read_le msg i i1
+  let i3 ← (↑(UScalar.cast .U64 len) : Result U64)
+  let i4 ← i3 <<< 56#i32
+  let i5 ← (↑(i2 ||| i4) : Result U64)
+  let state2 ← SipHash13.compress state1 i5
+  let (i6, _) ← SipHash13.finish state2
+  ok i6
+
 end sandbox
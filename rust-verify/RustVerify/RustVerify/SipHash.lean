@@ -0,0 +1,177 @@
+import Aeneas
+import RustVerify.Basic
+import RustVerify.BitSet
+
+open Aeneas.Std Result Error
+
+/-! `sandbox.siphash13` never panics and computes SipHash-1-3 as specified: one `sipround`
+per message word, including the last one padded with the length, and three to finish. -/
+
+namespace sandbox
+
+/-! Pure functional specification, on the state `(v0, v1, v2, v3)` as bit vectors. -/
+
+abbrev SipState := BitVec 64 × BitVec 64 × BitVec 64 × BitVec 64
+
+def sipround : SipState → SipState
+  | (v0, v1, v2, v3) =>
+    let v0 := v0 + v1
+    let v1 := v1.rotateLeft 13 ^^^ v0
+    let v0 := v0.rotateLeft 32
+    let v2 := v2 + v3
+    let v3 := v3.rotateLeft 16 ^^^ v2
+    let v0 := v0 + v3
+    let v3 := v3.rotateLeft 21 ^^^ v0
+    let v2 := v2 + v1
+    let v1 := v1.rotateLeft 17 ^^^ v2
+    (v0, v1, v2.rotateLeft 32, v3)
+
+def sip_init (k0 k1 : BitVec 64) : SipState :=
+  (k0 ^^^ 0x736f6d6570736575#64, k1 ^^^ 0x646f72616e646f6d#64,
+    k0 ^^^ 0x6c7967656e657261#64, k1 ^^^ 0x7465646279746573#64)
+
+def sip_compress (v : SipState) (m : BitVec 64) : SipState :=
+  let (v0, v1, v2, v3) := sipround (v.1, v.2.1, v.2.2.1, v.2.2.2 ^^^ m)
+  (v0 ^^^ m, v1, v2, v3)
+
+def sip_finish (v : SipState) : BitVec 64 :=
+  let v := (v.1, v.2.1, v.2.2.1 ^^^ 0xff#64, v.2.2.2)
+  let (v0, v1, v2, v3) := sipround (sipround (sipround v))
+  v0 ^^^ v1 ^^^ v2 ^^^ v3
+
+/-- The `n` bytes of `msg` from `start` on as a little-endian word. -/
+def le_word (msg : List U8) (start n : Nat) : BitVec 64 :=
+  (List.range n).foldl (fun w j => w ||| (msg[start + j]!.bv.zeroExtend 64 <<< (8 * j))) 0#64
+
+/-- The whole words of `msg`, then the rest of it padded with its length in the top byte. -/
+def siphash13_bv (k0 k1 : BitVec 64) (msg : List U8) : BitVec 64 :=
+  let words := msg.length / 8
+  let v := (List.range words).foldl (fun v k => sip_compress v (le_word msg (8 * k) 8))
+    (sip_init k0 k1)
+  let last := le_word msg (8 * words) (msg.length % 8) ||| BitVec.ofNat 64 msg.length <<< 56
+  sip_finish (sip_compress v last)
+
+/-- The empty message under the key 00..0f, as the reference implementation hashes it. -/
+example : sip_finish (sip_compress (sip_init 0x0706050403020100#64 0x0f0e0d0c0b0a0908#64) 0#64)
+    = 0xabac0158050fc4dc#64 := by decide
+
+def SipHash13.bv (self : SipHash13) : SipState :=
+  (self.v0.bv, self.v1.bv, self.v2.bv, self.v3.bv)
+
+/-! The model computes the specification, and never panics. -/
+
+@[progress]
+theorem rotl_spec (x : U64) (b : U32) (hb : 0 < b.val ∧ b.val < 64) :
+    ∃ y, rotl x b = ok y ∧ y.bv = x.bv.rotateLeft b.val := by
+  unfold rotl
+  progress as ⟨i, hi⟩
+  progress as ⟨i1, hi1⟩
+  progress as ⟨i2, hi2⟩
+  progress as ⟨y, hy, hy'⟩
+  simp_all [BitVec.rotateLeft_eq_shiftLeft_or_ushiftRight_of_lt]
+
+@[progress]
+theorem SipHash13.new_spec (k0 k1 : U64) :
+    ∃ state, SipHash13.new k0 k1 = ok state ∧ state.bv = sip_init k0.bv k1.bv := by
+  unfold SipHash13.new
+  progress*
+  simp_all [SipHash13.bv, sip_init]
+
+@[progress]
+theorem SipHash13.round_spec (self : SipHash13) :
+    ∃ self', self.round = ok self' ∧ self'.bv = sipround self.bv := by
+  unfold SipHash13.round
+  progress*
+  simp_all [SipHash13.bv, sipround, core.num.U64.wrapping_add_bv_eq]
+
+@[progress]
+theorem SipHash13.compress_spec (self : SipHash13) (m : U64) :
+    ∃ self', self.compress m = ok self' ∧ self'.bv = sip_compress self.bv m.bv := by
+  unfold SipHash13.compress
+  progress as ⟨i, hi⟩
+  progress as ⟨self1, hself1⟩
+  progress as ⟨i1, hi1⟩
+  simp_all [SipHash13.bv, sip_compress]
+
+@[progress]
+theorem SipHash13.finish_spec (self : SipHash13) :
+    ∃ h self', self.finish = ok (h, self') ∧ h.bv = sip_finish self.bv := by
+  unfold SipHash13.finish
+  progress*
+  simp_all [SipHash13.bv, sip_finish]
+
+@[progress]
+theorem read_le_loop_spec (msg : Slice U8) (start n : Usize) (word : U64) (j : Usize)
+    (hn : n.val ≤ 8) (hj : j.val ≤ n.val) (hmsg : start.val + n.val ≤ msg.length) :
+    ∃ w, read_le_loop msg start n word j = ok w ∧
+      w.bv = (List.range' j.val (n.val - j.val)).foldl
+        (fun w j => w ||| (msg.val[start.val + j]!.bv.zeroExtend 64 <<< (8 * j))) word.bv := by
+  unfold read_le_loop
+  split
+  · progress as ⟨i, hi⟩
+    progress as ⟨i1, hi1⟩
+    progress as ⟨i2, hi2⟩
+    progress as ⟨i3, hi3⟩
+    progress as ⟨i4, hi4⟩
+    progress as ⟨word1, hword1, hword1'⟩
+    progress as ⟨j1, hj1⟩
+    progress as ⟨w, hw⟩
+    rw [hw, show n.val - j.val = (n.val - j1.val) + 1 by scalar_tac, List.range'_succ,
+      List.foldl_cons]
+    simp_all [UScalar.cast, BitVec.setWidth_eq]
+    scalar_tac
+  · simp
+    scalar_tac
+termination_by n.val - j.val
+decreasing_by scalar_decr_tac
+
+@[progress]
+theorem read_le_spec (msg : Slice U8) (start n : Usize) (hn : n.val ≤ 8)
+    (hmsg : start.val + n.val ≤ msg.length) :
+    ∃ w, read_le msg start n = ok w ∧ w.bv = le_word msg.val start.val n.val := by
+  unfold read_le
+  progress as ⟨w, hw⟩
+  simp_all [le_word, List.range_eq_range']
+
+/-- The loop compresses the whole words from `i` on. -/
+@[progress]
+theorem siphash13_loop_spec (msg : Slice U8) (len : Usize) (state : SipHash13) (i : Usize)
+    (hlen : len.val = msg.length) (hi : i.val ≤ len.val) (hword : 8 ∣ i.val) :
+    ∃ state' i', siphash13_loop msg len state i = ok (state', i') ∧
+      i'.val = 8 * (len.val / 8) ∧
+      state'.bv = (List.range' (i.val / 8) (len.val / 8 - i.val / 8)).foldl
+        (fun v k => sip_compress v (le_word msg.val (8 * k) 8)) state.bv := by
+  unfold siphash13_loop
+  progress as ⟨i1, hi1⟩
+  split
+  · progress as ⟨i2, hi2⟩
+    progress as ⟨state1, hstate1⟩
+    progress as ⟨i3, hi3⟩
+    progress as ⟨state', i', hi', hstate'⟩
+    refine ⟨hi', ?_⟩
+    rw [hstate', show len.val / 8 - i.val / 8 = (len.val / 8 - i3.val / 8) + 1 by omega,
+      List.range'_succ, List.foldl_cons, hstate1, hi2]
+    congr 2
+    omega
+  · simp
+    omega
+termination_by len.val - i.val
+decreasing_by scalar_decr_tac
+
+theorem siphash13_spec (k0 k1 : U64) (msg : Slice U8) :
+    ∃ h, siphash13 k0 k1 msg = ok h ∧ h.bv = siphash13_bv k0.bv k1.bv msg.val := by
+  unfold siphash13
+  progress as ⟨state, hstate⟩
+  progress as ⟨state1, i, hi, hstate1⟩
+  progress as ⟨i1, hi1⟩
+  progress as ⟨i2, hi2⟩
+  progress as ⟨i3, hi3⟩
+  progress as ⟨i4, hi4⟩
+  progress as ⟨i5, hi5, hi5'⟩
+  progress as ⟨state2, hstate2⟩
+  progress as ⟨h, state3, hh⟩
+  simp_all [siphash13_bv, List.range_eq_range', UScalar.cast, Slice.length]
+  congr 3
+  omega
+
+end sandbox
@@ -0,0 +1,16 @@
+import Lake
+open Lake DSL
+
+package RustVerify where
+  version := v!"0.1.0"
+
+-- The Lean backend of an Aeneas checkout next to this repository, or the one given by
+-- `lake -K aeneas=<dir>`
+require aeneas from
+  System.FilePath.mk ((get_config? aeneas).getD "../../../aeneas/backends/lean")
+
+lean_lib RustVerify
+
+@[default_target]
+lean_exe rustverify where
+  root := `Main
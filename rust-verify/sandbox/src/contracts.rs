@@ -0,0 +1,526 @@
+//! The theorems of `RustVerify` as assertions on concrete inputs, checked on many inputs by
+//! `cargo test` and on all of them by `cargo kani`.
+//!
+//! Each function is named after its theorem and panics if it does not hold, so a
+//! discrepancy between the Rust code and the Lean specs shows up without running Lean.
+
+use super::*;
+
+fn fits_i32(x: i128) -> bool {
+    i32::MIN as i128 <= x && x <= i32::MAX as i128
+}
+
+/// `add_spec`
+pub fn add_spec(a: i32, b: i32) {
+    let sum = a as i128 + b as i128;
+    if fits_i32(sum) {
+        assert_eq!(add(a, b) as i128, sum);
+    }
+}
+
+/// `fma_spec`
+pub fn fma_spec(a: i32, b: i32, c: i32) {
+    let product = a as i128 * b as i128;
+    if fits_i32(product) && fits_i32(product + c as i128) {
+        assert_eq!(fma(a, b, c) as i128, product + c as i128);
+    }
+}
+
+/// `narrow_spec`
+pub fn narrow_spec(x: i64) {
+    match narrow(x) {
+        Some(c) => assert_eq!(c as i64, x),
+        None => assert!(!fits_i32(x as i128)),
+    }
+}
+
+/// `saturate_spec`
+pub fn saturate_spec(x: i64) {
+    assert_eq!(
+        saturate(x) as i64,
+        x.clamp(i32::MIN as i64, i32::MAX as i64)
+    );
+}
+
+/// `checked_add_spec`
+pub fn checked_add_spec(a: i32, b: i32) {
+    let sum = a as i128 + b as i128;
+    match checked_add(a, b) {
+        Some(c) => assert_eq!(c as i128, sum),
+        None => assert!(!fits_i32(sum)),
+    }
+}
+
+/// `checked_fma_spec`
+pub fn checked_fma_spec(a: i32, b: i32, c: i32) {
+    let exact = a as i128 * b as i128 + c as i128;
+    match checked_fma(a, b, c) {
+        Some(d) => assert_eq!(d as i128, exact),
+        None => assert!(!fits_i32(exact)),
+    }
+}
+
+/// `saturating_add_spec`
+pub fn saturating_add_spec(a: i32, b: i32) {
+    let sum = a as i128 + b as i128;
+    let clamped = sum.clamp(i32::MIN as i128, i32::MAX as i128);
+    assert_eq!(saturating_add(a, b) as i128, clamped);
+}
+
+/// `saturating_fma_spec`
+pub fn saturating_fma_spec(a: i32, b: i32, c: i32) {
+    let exact = a as i128 * b as i128 + c as i128;
+    let clamped = exact.clamp(i32::MIN as i128, i32::MAX as i128);
+    assert_eq!(saturating_fma(a, b, c) as i128, clamped);
+}
+
+/// `wrapping_add_spec`, where `c.bv = a.bv + b.bv` is the sum modulo 2^32
+pub fn wrapping_add_spec(a: i32, b: i32) {
+    assert_eq!(wrapping_add(a, b), (a as i128 + b as i128) as i32);
+}
+
+/// `wrapping_fma_spec`
+pub fn wrapping_fma_spec(a: i32, b: i32, c: i32) {
+    assert_eq!(
+        wrapping_fma(a, b, c),
+        (a as i128 * b as i128 + c as i128) as i32
+    );
+}
+
+/// `Pcg64Si.next_u64_no_panic`
+pub fn pcg64si_next_u64_no_panic(state: u64) {
+    Pcg64Si { state }.next_u64();
+}
+
+/// The pure `pcg32si_step` of `Basic.lean`.
+fn pcg32si_step(state: u32) -> u32 {
+    state.wrapping_mul(747796405).wrapping_add(2891336453)
+}
+
+/// The pure `pcg32si_output` of `Basic.lean`.
+fn pcg32si_output(state: u32) -> u32 {
+    let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+    (word >> 22) ^ word
+}
+
+/// `PCG32si.new` is `pcg32si_new`
+pub fn pcg32si_new_spec(seed: u32) {
+    let rng = PCG32si::new(seed);
+    assert_eq!(
+        rng.state,
+        pcg32si_step(pcg32si_step(seed).wrapping_add(seed))
+    );
+}
+
+/// `PCG32si.next_u32` is `pcg32si_output` of the old state and `pcg32si_step` of it
+pub fn pcg32si_next_u32_spec(state: u32) {
+    let mut rng = PCG32si { state };
+    assert_eq!(rng.next_u32(), pcg32si_output(state));
+    assert_eq!(rng.state, pcg32si_step(state));
+}
+
+/// `Xorshift32.next_u32_no_panic`, and the output and the new state are `xorshift32`
+pub fn xorshift32_next_u32_spec(state: u32) {
+    let mut rng = Xorshift32 { state };
+    let x = state ^ (state << 13);
+    let x = x ^ (x >> 17);
+    let expected = x ^ (x << 5);
+    assert_eq!(rng.next_u32(), expected);
+    assert_eq!(rng.state, expected);
+}
+
+/// `Xorshift64.next_u64_no_panic`, and the output and the new state are `xorshift64`
+pub fn xorshift64_next_u64_spec(state: u64) {
+    let mut rng = Xorshift64 { state };
+    let x = state ^ (state << 13);
+    let x = x ^ (x >> 7);
+    let expected = x ^ (x << 17);
+    assert_eq!(rng.next_u64(), expected);
+    assert_eq!(rng.state, expected);
+}
+
+/// `Xorshift128.next_u32_no_panic`, and the new state is `xorshift128`
+pub fn xorshift128_next_u32_spec(x: u32, y: u32, z: u32, w: u32) {
+    let mut rng = Xorshift128 { x, y, z, w };
+    let t = x ^ (x << 11);
+    let expected = w ^ (w >> 19) ^ t ^ (t >> 8);
+    assert_eq!(rng.next_u32(), expected);
+    assert_eq!((rng.x, rng.y, rng.z, rng.w), (y, z, w, expected));
+}
+
+/// `matrix_rank_spec`
+pub fn matrix_rank_spec(matrix: &[u32]) {
+    let mut copy = matrix.to_vec();
+    let rank = matrix_rank(&mut copy);
+    assert!(rank <= matrix.len().min(32));
+    assert_eq!(copy.len(), matrix.len());
+}
+
+/// `RingBuffer.inv`, the bounds under which `push` and `pop` never index out of `buf`.
+fn ring_buffer_inv(ring: &RingBuffer) {
+    assert!(ring.head < RingBuffer::CAPACITY);
+    assert!(ring.size <= RingBuffer::CAPACITY);
+}
+
+/// `RingBuffer.toList`, the values from the front to the back.
+fn ring_buffer_to_list(ring: &RingBuffer) -> Vec<u32> {
+    (0..ring.size)
+        .map(|k| ring.buf[(ring.head + k) % RingBuffer::CAPACITY])
+        .collect()
+}
+
+/// `RingBuffer.new_spec`, `push_spec`, `pop_spec` and `len_spec` along `ops`, where
+/// `Some(value)` pushes `value` and `None` pops.
+pub fn ring_buffer_spec(ops: &[Option<u32>]) {
+    let mut ring = RingBuffer::new();
+    ring_buffer_inv(&ring);
+    assert!(ring_buffer_to_list(&ring).is_empty());
+    for &op in ops {
+        let mut list = ring_buffer_to_list(&ring);
+        match op {
+            Some(value) => {
+                let pushed = ring.push(value);
+                assert_eq!(pushed, list.len() < RingBuffer::CAPACITY);
+                if pushed {
+                    list.push(value);
+                }
+            }
+            None => {
+                let front = (!list.is_empty()).then(|| list.remove(0));
+                assert_eq!(ring.pop(), front);
+            }
+        }
+        ring_buffer_inv(&ring);
+        assert_eq!(ring_buffer_to_list(&ring), list);
+        assert_eq!(ring.len(), list.len());
+    }
+}
+
+/// `BitSet64.set_spec`, `clear_spec` and `test_spec`, and how `set` and `clear` change
+/// `test` of `j`: `test_set` and `test_clear`.
+pub fn bitset_spec(bits: u64, i: u32, j: u32) {
+    if i < 64 && j < 64 {
+        let test = |set: &BitSet64| set.test(j);
+        assert_eq!(test(&BitSet64 { bits }), bits >> j & 1 == 1);
+
+        let mut set = BitSet64 { bits };
+        set.set(i);
+        assert_eq!(set.bits, bits | 1 << i);
+        assert_eq!(test(&set), i == j || test(&BitSet64 { bits }));
+
+        let mut set = BitSet64 { bits };
+        set.clear(i);
+        assert_eq!(set.bits, bits & !(1 << i));
+        assert_eq!(test(&set), i != j && test(&BitSet64 { bits }));
+    }
+}
+
+/// `BitSet64.count_spec`
+pub fn bitset_count_spec(bits: u64) {
+    let count = BitSet64 { bits }.count();
+    assert_eq!(
+        count,
+        (0..64).filter(|&i| bits >> i & 1 == 1).count() as u32
+    );
+    assert!(count <= 64);
+}
+
+/// The pure `sipround` of `SipHash.lean`.
+fn sipround([mut v0, mut v1, mut v2, mut v3]: [u64; 4]) -> [u64; 4] {
+    v0 = v0.wrapping_add(v1);
+    v1 = v1.rotate_left(13) ^ v0;
+    v0 = v0.rotate_left(32);
+    v2 = v2.wrapping_add(v3);
+    v3 = v3.rotate_left(16) ^ v2;
+    v0 = v0.wrapping_add(v3);
+    v3 = v3.rotate_left(21) ^ v0;
+    v2 = v2.wrapping_add(v1);
+    v1 = v1.rotate_left(17) ^ v2;
+    [v0, v1, v2.rotate_left(32), v3]
+}
+
+/// The pure `siphash13_bv` of `SipHash.lean`, over whole words and the padded last one.
+fn siphash13_reference(k0: u64, k1: u64, msg: &[u8]) -> u64 {
+    let mut v = [
+        k0 ^ 0x736f6d6570736575,
+        k1 ^ 0x646f72616e646f6d,
+        k0 ^ 0x6c7967656e657261,
+        k1 ^ 0x7465646279746573,
+    ];
+    let mut compress = |m: u64| {
+        v[3] ^= m;
+        v = sipround(v);
+        v[0] ^= m;
+    };
+    let chunks = msg.chunks_exact(8);
+    let mut last = [0; 8];
+    last[..chunks.remainder().len()].copy_from_slice(chunks.remainder());
+    for chunk in chunks {
+        compress(u64::from_le_bytes(chunk.try_into().unwrap()));
+    }
+    compress(u64::from_le_bytes(last) | (msg.len() as u64) << 56);
+    v[2] ^= 0xff;
+    let [v0, v1, v2, v3] = sipround(sipround(sipround(v)));
+    v0 ^ v1 ^ v2 ^ v3
+}
+
+/// `SipHash13.round_spec`, with the state as `[v0, v1, v2, v3]`
+pub fn siphash13_round_spec(v: [u64; 4]) {
+    let [v0, v1, v2, v3] = v;
+    let mut state = SipHash13 { v0, v1, v2, v3 };
+    state.round();
+    assert_eq!([state.v0, state.v1, state.v2, state.v3], sipround(v));
+}
+
+/// `read_le_spec`, for `n` up to 8 bytes within `msg`
+pub fn read_le_spec(msg: &[u8], start: usize, n: usize) {
+    if n <= 8 && start <= msg.len() && n <= msg.len() - start {
+        let mut bytes = [0; 8];
+        bytes[..n].copy_from_slice(&msg[start..start + n]);
+        assert_eq!(read_le(msg, start, n), u64::from_le_bytes(bytes));
+    }
+}
+
+/// `siphash13_spec`
+pub fn siphash13_spec(k0: u64, k1: u64, msg: &[u8]) {
+    assert_eq!(siphash13(k0, k1, msg), siphash13_reference(k0, k1, msg));
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// Values around the edges of overflow, where the contracts change.
+    const EDGES: [i32; 12] = [
+        i32::MIN,
+        i32::MIN + 1,
+        -46341,
+        -46340,
+        -2,
+        -1,
+        0,
+        1,
+        2,
+        46340,
+        46341,
+        i32::MAX,
+    ];
+
+    #[test]
+    fn exhaustive_edges() {
+        for a in EDGES {
+            for b in EDGES {
+                add_spec(a, b);
+                checked_add_spec(a, b);
+                saturating_add_spec(a, b);
+                wrapping_add_spec(a, b);
+                for c in EDGES {
+                    fma_spec(a, b, c);
+                    checked_fma_spec(a, b, c);
+                    saturating_fma_spec(a, b, c);
+                    wrapping_fma_spec(a, b, c);
+                }
+            }
+        }
+
+        for edge in [i32::MIN as i64, i32::MAX as i64, i64::MIN, i64::MAX] {
+            for x in edge.saturating_sub(2)..=edge.saturating_add(2) {
+                narrow_spec(x);
+                saturate_spec(x);
+            }
+        }
+    }
+
+    #[test]
+    fn exhaustive_low_states() {
+        for state in 0..1 << 16 {
+            pcg32si_new_spec(state);
+            pcg32si_next_u32_spec(state);
+            xorshift32_next_u32_spec(state);
+            xorshift32_next_u32_spec(!state);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10_000))]
+
+        #[test]
+        fn arithmetic(a: i32, b: i32, c: i32) {
+            add_spec(a, b);
+            fma_spec(a, b, c);
+            checked_add_spec(a, b);
+            checked_fma_spec(a, b, c);
+            saturating_add_spec(a, b);
+            saturating_fma_spec(a, b, c);
+            wrapping_add_spec(a, b);
+            wrapping_fma_spec(a, b, c);
+        }
+
+        #[test]
+        fn small_arithmetic(a in -1 << 16..1 << 16, b in -1 << 16..1 << 16, c: i32) {
+            fma_spec(a, b, c);
+            checked_fma_spec(a, b, c);
+            saturating_fma_spec(a, b, c);
+        }
+
+        #[test]
+        fn narrowing(x: i64, y in i32::MIN as i64 - 1000..i32::MAX as i64 + 1000) {
+            narrow_spec(x);
+            narrow_spec(y);
+            saturate_spec(x);
+            saturate_spec(y);
+        }
+
+        #[test]
+        fn generators(state64: u64, state: u32, y: u32, z: u32, w: u32) {
+            pcg64si_next_u64_no_panic(state64);
+            pcg32si_new_spec(state);
+            pcg32si_next_u32_spec(state);
+            xorshift32_next_u32_spec(state);
+            xorshift64_next_u64_spec(state64);
+            xorshift128_next_u32_spec(state, y, z, w);
+        }
+    }
+
+    #[test]
+    fn exhaustive_bitset_indices() {
+        for bits in [0, 1, 1 << 63, 0x5555_5555_5555_5555, u64::MAX] {
+            bitset_count_spec(bits);
+            for i in 0..64 {
+                for j in 0..64 {
+                    bitset_spec(bits, i, j);
+                }
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn siphash13(k0: u64, k1: u64, msg in prop::collection::vec(any::<u8>(), 0..100)) {
+            siphash13_spec(k0, k1, &msg);
+        }
+
+        #[test]
+        fn siphash13_parts(v: [u64; 4], msg: [u8; 16], start in 0usize..17, n in 0usize..9) {
+            siphash13_round_spec(v);
+            read_le_spec(&msg, start, n);
+        }
+
+        #[test]
+        fn ring_buffer(ops in prop::collection::vec(any::<Option<u32>>(), 0..100)) {
+            ring_buffer_spec(&ops);
+        }
+
+        #[test]
+        fn bitset(bits: u64, i in 0u32..64, j in 0u32..64) {
+            bitset_spec(bits, i, j);
+            bitset_count_spec(bits);
+        }
+
+        #[test]
+        fn matrix_rank(matrix in prop::collection::vec(any::<u32>(), 0..70)) {
+            matrix_rank_spec(&matrix);
+        }
+
+        #[test]
+        fn sparse_matrix_rank(matrix in prop::collection::vec(0u32..1 << 8, 0..40)) {
+            matrix_rank_spec(&matrix);
+        }
+    }
+}
+
+#[cfg(kani)]
+mod proofs {
+    use super::*;
+
+    #[kani::proof]
+    fn add() {
+        add_spec(kani::any(), kani::any());
+    }
+
+    #[kani::proof]
+    fn fma() {
+        fma_spec(kani::any(), kani::any(), kani::any());
+    }
+
+    #[kani::proof]
+    fn narrow() {
+        narrow_spec(kani::any());
+    }
+
+    #[kani::proof]
+    fn saturate() {
+        saturate_spec(kani::any());
+    }
+
+    #[kani::proof]
+    fn checked() {
+        checked_add_spec(kani::any(), kani::any());
+        checked_fma_spec(kani::any(), kani::any(), kani::any());
+    }
+
+    #[kani::proof]
+    fn saturating() {
+        saturating_add_spec(kani::any(), kani::any());
+        saturating_fma_spec(kani::any(), kani::any(), kani::any());
+    }
+
+    #[kani::proof]
+    fn wrapping() {
+        wrapping_add_spec(kani::any(), kani::any());
+        wrapping_fma_spec(kani::any(), kani::any(), kani::any());
+    }
+
+    #[kani::proof]
+    fn generators() {
+        pcg64si_next_u64_no_panic(kani::any());
+        pcg32si_new_spec(kani::any());
+        pcg32si_next_u32_spec(kani::any());
+        xorshift32_next_u32_spec(kani::any());
+        xorshift64_next_u64_spec(kani::any());
+        xorshift128_next_u32_spec(kani::any(), kani::any(), kani::any(), kani::any());
+    }
+
+    /// Bounded to 20 operations, enough to fill the buffer and wrap around.
+    #[kani::proof]
+    #[kani::unwind(21)]
+    fn ring_buffer() {
+        let ops: [Option<u32>; 20] = kani::any();
+        ring_buffer_spec(&ops);
+    }
+
+    #[kani::proof]
+    #[kani::unwind(65)]
+    fn bitset() {
+        bitset_spec(kani::any(), kani::any(), kani::any());
+        bitset_count_spec(kani::any());
+    }
+
+    #[kani::proof]
+    #[kani::unwind(9)]
+    fn siphash13_parts() {
+        siphash13_round_spec(kani::any());
+        let msg: [u8; 8] = kani::any();
+        read_le_spec(&msg, kani::any(), kani::any());
+    }
+
+    /// Bounded to messages of up to 12 bytes, one whole word and a partial one.
+    #[kani::proof]
+    #[kani::unwind(13)]
+    fn siphash13() {
+        let msg: [u8; 12] = kani::any();
+        let len: usize = kani::any_where(|&len| len <= 12);
+        siphash13_spec(kani::any(), kani::any(), &msg[..len]);
+    }
+
+    /// Bounded to 4 rows, for 32 iterations of the outer loop over up to 4 rows each.
+    #[kani::proof]
+    #[kani::unwind(34)]
+    fn matrix_rank() {
+        let matrix: [u32; 4] = kani::any();
+        let rows: usize = kani::any_where(|&rows| rows <= 4);
+        matrix_rank_spec(&matrix[..rows]);
+    }
+}
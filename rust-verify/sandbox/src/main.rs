@@ -8,6 +8,57 @@ pub fn fma(a: i32, b: i32, c: i32) -> i32 {
     a * b + c
 }
 
+/// `x` if it fits in an `i32`.
+// Without `contains`, as Aeneas does not translate ranges
+#[allow(clippy::manual_range_contains)]
+fn narrow(x: i64) -> Option<i32> {
+    if i32::MIN as i64 <= x && x <= i32::MAX as i64 {
+        Some(x as i32)
+    } else {
+        None
+    }
+}
+
+/// `x` clamped to the range of `i32`.
+fn saturate(x: i64) -> i32 {
+    if x < i32::MIN as i64 {
+        i32::MIN
+    } else if x > i32::MAX as i64 {
+        i32::MAX
+    } else {
+        x as i32
+    }
+}
+
+/// `a + b`, or `None` if it overflows.
+pub fn checked_add(a: i32, b: i32) -> Option<i32> {
+    narrow(a as i64 + b as i64)
+}
+
+/// `a * b + c`, or `None` if it overflows, which unlike `a.checked_mul(b)?.checked_add(c)`
+/// allows `a * b` alone to overflow.
+pub fn checked_fma(a: i32, b: i32, c: i32) -> Option<i32> {
+    narrow(a as i64 * b as i64 + c as i64)
+}
+
+pub fn saturating_add(a: i32, b: i32) -> i32 {
+    saturate(a as i64 + b as i64)
+}
+
+/// `a * b + c` clamped to the range of `i32`, which `a.saturating_mul(b).saturating_add(c)`
+/// is not when `a * b` is clamped.
+pub fn saturating_fma(a: i32, b: i32, c: i32) -> i32 {
+    saturate(a as i64 * b as i64 + c as i64)
+}
+
+pub fn wrapping_add(a: i32, b: i32) -> i32 {
+    a.wrapping_add(b)
+}
+
+pub fn wrapping_fma(a: i32, b: i32, c: i32) -> i32 {
+    a.wrapping_mul(b).wrapping_add(c)
+}
+
 #[repr(transparent)]
 pub struct Pcg64Si {
     state: u64,
@@ -26,3 +77,577 @@ impl Pcg64Si {
         (word >> 43) ^ word
     }
 }
+
+/// `PCG32si` of `raytracing-example/shader/src/rand.rs` without its float outputs.
+#[repr(transparent)]
+pub struct PCG32si {
+    state: u32,
+}
+
+impl PCG32si {
+    const PCG_DEFAULT_MULTIPLIER_32: u32 = 747796405;
+    const PCG_DEFAULT_INCREMENT_32: u32 = 2891336453;
+
+    fn pcg_oneseq_32_step_r(&mut self) {
+        self.state = self
+            .state
+            .wrapping_mul(Self::PCG_DEFAULT_MULTIPLIER_32)
+            .wrapping_add(Self::PCG_DEFAULT_INCREMENT_32);
+    }
+
+    fn pcg_output_rxs_m_xs_32_32(state: u32) -> u32 {
+        let word = ((state >> ((state >> 28).wrapping_add(4))) ^ state).wrapping_mul(277803737);
+        (word >> 22) ^ word
+    }
+
+    pub fn new(seed: u32) -> Self {
+        let mut rng = Self { state: seed };
+        rng.pcg_oneseq_32_step_r();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.pcg_oneseq_32_step_r();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old_state = self.state;
+        self.pcg_oneseq_32_step_r();
+        Self::pcg_output_rxs_m_xs_32_32(old_state)
+    }
+}
+
+#[repr(transparent)]
+pub struct Xorshift32 {
+    state: u32,
+}
+
+impl Xorshift32 {
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+}
+
+#[repr(transparent)]
+pub struct Xorshift64 {
+    state: u64,
+}
+
+impl Xorshift64 {
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+}
+
+pub struct Xorshift128 {
+    x: u32,
+    y: u32,
+    z: u32,
+    w: u32,
+}
+
+impl Xorshift128 {
+    pub fn next_u32(&mut self) -> u32 {
+        let t = self.x ^ (self.x << 11);
+        self.x = self.y;
+        self.y = self.z;
+        self.z = self.w;
+        self.w = self.w ^ (self.w >> 19) ^ t ^ (t >> 8);
+        self.w
+    }
+}
+
+/// `matrix_rank` of `matrix_rank_gf2` in the subset Aeneas translates: explicit loops and
+/// indexing, without iterator adaptors, closures or `swap`.
+#[allow(clippy::manual_swap)]
+pub fn matrix_rank(matrix: &mut [u32]) -> usize {
+    let n = matrix.len();
+    let mut rank = 0;
+    let mut bit: u32 = 0;
+    while bit < 32 {
+        let mut pivot = rank;
+        while pivot < n && (matrix[pivot] >> bit) & 1 == 0 {
+            pivot += 1;
+        }
+        if pivot < n {
+            let row = matrix[pivot];
+            matrix[pivot] = matrix[rank];
+            matrix[rank] = row;
+            let mut i = 0;
+            while i < n {
+                if i != rank && (matrix[i] >> bit) & 1 == 1 {
+                    matrix[i] ^= row;
+                }
+                i += 1;
+            }
+            rank += 1;
+        }
+        bit += 1;
+    }
+    rank
+}
+
+/// A FIFO queue of up to `CAPACITY` values, the `size` of them stored from `head` on and
+/// wrapping around.
+pub struct RingBuffer {
+    buf: [u32; 16],
+    head: usize,
+    size: usize,
+}
+
+// Without `Default`, to keep trait impls out of the Lean model
+#[allow(clippy::new_without_default)]
+impl RingBuffer {
+    const CAPACITY: usize = 16;
+
+    pub fn new() -> Self {
+        RingBuffer {
+            buf: [0; 16],
+            head: 0,
+            size: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Appends `value` at the back, or returns `false` without it if the buffer is full.
+    pub fn push(&mut self, value: u32) -> bool {
+        if self.size == Self::CAPACITY {
+            false
+        } else {
+            self.buf[(self.head + self.size) % Self::CAPACITY] = value;
+            self.size += 1;
+            true
+        }
+    }
+
+    /// Removes the value at the front.
+    pub fn pop(&mut self) -> Option<u32> {
+        if self.size == 0 {
+            None
+        } else {
+            let value = self.buf[self.head];
+            self.head = (self.head + 1) % Self::CAPACITY;
+            self.size -= 1;
+            Some(value)
+        }
+    }
+}
+
+/// A set of integers below 64, one bit each.
+#[repr(transparent)]
+pub struct BitSet64 {
+    bits: u64,
+}
+
+// Without `Default`, to keep trait impls out of the Lean model
+#[allow(clippy::new_without_default)]
+impl BitSet64 {
+    pub fn new() -> Self {
+        BitSet64 { bits: 0 }
+    }
+
+    /// Adds `i`, which panics unless it is below 64.
+    pub fn set(&mut self, i: u32) {
+        self.bits |= 1 << i;
+    }
+
+    /// Removes `i`, which panics unless it is below 64.
+    pub fn clear(&mut self, i: u32) {
+        self.bits &= !(1 << i);
+    }
+
+    /// Whether `i` is in the set, which panics unless it is below 64.
+    pub fn test(&self, i: u32) -> bool {
+        (self.bits >> i) & 1 == 1
+    }
+
+    /// The number of elements, with a loop instead of `count_ones` for Aeneas.
+    pub fn count(&self) -> u32 {
+        let mut count = 0;
+        let mut i = 0;
+        while i < 64 {
+            count += ((self.bits >> i) & 1) as u32;
+            i += 1;
+        }
+        count
+    }
+}
+
+/// `x` rotated left by `b`, which panics unless it is in `1..64`.
+// With shifts rather than `rotate_left`, so that `rotl_spec` only needs the shift lemmas the
+// other specs use. Whether Aeneas translates `rotate_left` itself is not checked yet.
+#[allow(clippy::manual_rotate)]
+fn rotl(x: u64, b: u32) -> u64 {
+    (x << b) | (x >> (64 - b))
+}
+
+/// The state of SipHash-1-3: one round per message word and three to finish.
+pub struct SipHash13 {
+    v0: u64,
+    v1: u64,
+    v2: u64,
+    v3: u64,
+}
+
+impl SipHash13 {
+    fn new(k0: u64, k1: u64) -> Self {
+        SipHash13 {
+            v0: k0 ^ 0x736f6d6570736575,
+            v1: k1 ^ 0x646f72616e646f6d,
+            v2: k0 ^ 0x6c7967656e657261,
+            v3: k1 ^ 0x7465646279746573,
+        }
+    }
+
+    fn round(&mut self) {
+        self.v0 = self.v0.wrapping_add(self.v1);
+        self.v1 = rotl(self.v1, 13);
+        self.v1 ^= self.v0;
+        self.v0 = rotl(self.v0, 32);
+        self.v2 = self.v2.wrapping_add(self.v3);
+        self.v3 = rotl(self.v3, 16);
+        self.v3 ^= self.v2;
+        self.v0 = self.v0.wrapping_add(self.v3);
+        self.v3 = rotl(self.v3, 21);
+        self.v3 ^= self.v0;
+        self.v2 = self.v2.wrapping_add(self.v1);
+        self.v1 = rotl(self.v1, 17);
+        self.v1 ^= self.v2;
+        self.v2 = rotl(self.v2, 32);
+    }
+
+    fn compress(&mut self, m: u64) {
+        self.v3 ^= m;
+        self.round();
+        self.v0 ^= m;
+    }
+
+    fn finish(&mut self) -> u64 {
+        self.v2 ^= 0xff;
+        self.round();
+        self.round();
+        self.round();
+        self.v0 ^ self.v1 ^ self.v2 ^ self.v3
+    }
+}
+
+/// The `n` bytes of `msg` from `start` on as a little-endian word.
+fn read_le(msg: &[u8], start: usize, n: usize) -> u64 {
+    let mut word = 0;
+    let mut j = 0;
+    while j < n {
+        word |= (msg[start + j] as u64) << (8 * j);
+        j += 1;
+    }
+    word
+}
+
+/// SipHash-1-3 of `msg` under the key `(k0, k1)`.
+pub fn siphash13(k0: u64, k1: u64, msg: &[u8]) -> u64 {
+    let len = msg.len();
+    let mut state = SipHash13::new(k0, k1);
+    let mut i = 0;
+    // Without `i + 8 <= len`, which could overflow
+    while len - i >= 8 {
+        state.compress(read_le(msg, i, 8));
+        i += 8;
+    }
+    state.compress(read_le(msg, i, len - i) | (len as u64) << 56);
+    state.finish()
+}
+
+#[cfg(any(test, kani))]
+mod contracts;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pcg32si() {
+        let mut rng = PCG32si::new(0);
+        let outputs = [0; 5].map(|_| rng.next_u32());
+        assert_eq!(
+            outputs,
+            [582399676, 1006035121, 1462727737, 2264681948, 879071586]
+        );
+
+        let mut rng = PCG32si::new(42);
+        let outputs = [0; 5].map(|_| rng.next_u32());
+        assert_eq!(
+            outputs,
+            [472323697, 765726045, 2298728223, 2345884738, 2982542781]
+        );
+    }
+
+    #[test]
+    fn test_xorshift() {
+        // The examples of Marsaglia's paper "Xorshift RNGs"
+        let mut rng = Xorshift32 { state: 2463534242 };
+        let outputs = [0; 3].map(|_| rng.next_u32());
+        assert_eq!(outputs, [723471715, 2497366906, 2064144800]);
+
+        let mut rng = Xorshift64 {
+            state: 88172645463325252,
+        };
+        let outputs = [0; 3].map(|_| rng.next_u64());
+        assert_eq!(
+            outputs,
+            [
+                8748534153485358512,
+                3040900993826735515,
+                3453997556048239312
+            ]
+        );
+
+        let mut rng = Xorshift128 {
+            x: 123456789,
+            y: 362436069,
+            z: 521288629,
+            w: 88675123,
+        };
+        let outputs = [0; 5].map(|_| rng.next_u32());
+        assert_eq!(
+            outputs,
+            [3701687786, 458299110, 2500872618, 3633119408, 516391518]
+        );
+    }
+
+    #[test]
+    fn test_checked() {
+        let edges = [
+            i32::MIN,
+            i32::MIN + 1,
+            -65536,
+            -2,
+            -1,
+            0,
+            1,
+            2,
+            46341,
+            i32::MAX,
+        ];
+        for a in edges {
+            for b in edges {
+                assert_eq!(checked_add(a, b), a.checked_add(b));
+                assert_eq!(saturating_add(a, b), a.saturating_add(b));
+                assert_eq!(wrapping_add(a, b), a.wrapping_add(b));
+                for c in edges {
+                    let exact = a as i128 * b as i128 + c as i128;
+                    assert_eq!(checked_fma(a, b, c), i32::try_from(exact).ok());
+                    assert_eq!(
+                        saturating_fma(a, b, c) as i128,
+                        exact.clamp(i32::MIN as i128, i32::MAX as i128)
+                    );
+                    assert_eq!(wrapping_fma(a, b, c), exact as i32);
+                }
+            }
+        }
+
+        // a * b overflows but a * b + c does not
+        assert_eq!(checked_fma(65536, 32768, -1), Some(i32::MAX));
+        assert_eq!(saturating_fma(65536, 32768, -1), i32::MAX);
+        assert_eq!(saturating_fma(65536, 65536, i32::MIN), i32::MAX);
+    }
+
+    /// The original `matrix_rank` of `matrix_rank_gf2`.
+    fn reference_rank(matrix: &mut [u32]) -> usize {
+        let n = matrix.len();
+        let mut rank = 0;
+        for bit in 0..u32::BITS {
+            let pivot = (rank..n).find(|&i| (matrix[i] >> bit) & 1 == 1);
+            if let Some(pivot) = pivot {
+                matrix.swap(rank, pivot);
+                for i in 0..n {
+                    if i != rank && (matrix[i] >> bit) & 1 == 1 {
+                        matrix[i] ^= matrix[rank];
+                    }
+                }
+                rank += 1;
+            }
+        }
+        rank
+    }
+
+    #[test]
+    fn test_matrix_rank() {
+        assert_eq!(matrix_rank(&mut [0b1100, 0b1010, 0b1001]), 3);
+        assert_eq!(matrix_rank(&mut [0b1100, 0b1100, 0b1100]), 1);
+        assert_eq!(matrix_rank(&mut [0, 0, 0]), 0);
+        assert_eq!(matrix_rank(&mut []), 0);
+
+        let mut rng = PCG32si::new(1);
+        for n in 0..70 {
+            for sparse in [false, true] {
+                let mut matrix = (0..n)
+                    .map(|_| {
+                        let row = rng.next_u32();
+                        if sparse {
+                            row & rng.next_u32() & rng.next_u32()
+                        } else {
+                            row
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let mut expected = matrix.clone();
+                let rank = matrix_rank(&mut matrix);
+                assert_eq!(rank, reference_rank(&mut expected));
+                assert!(rank <= n.min(32));
+                assert_eq!(matrix, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ring_buffer() {
+        let mut ring = RingBuffer::new();
+        assert!(ring.is_empty());
+        assert_eq!(ring.pop(), None);
+
+        // Wrapping around the end of `buf` several times
+        let mut next = 0;
+        for _ in 0..5 {
+            for value in next..next + 11 {
+                assert!(ring.push(value));
+            }
+            assert_eq!(ring.len(), 11);
+            for value in next..next + 11 {
+                assert_eq!(ring.pop(), Some(value));
+            }
+            next += 11;
+        }
+        assert!(ring.is_empty());
+
+        while ring.push(next) {
+            next += 1;
+        }
+        assert_eq!(ring.len(), RingBuffer::CAPACITY);
+        assert!(!ring.push(next));
+        let values = (0..RingBuffer::CAPACITY).map(|_| ring.pop().unwrap());
+        assert!(values.eq(55..71));
+        assert_eq!(ring.pop(), None);
+    }
+
+    #[test]
+    fn test_bitset() {
+        let mut set = BitSet64::new();
+        assert_eq!(set.count(), 0);
+        for i in [0, 1, 31, 32, 63, 1] {
+            set.set(i);
+        }
+        assert_eq!(set.bits, 1 << 63 | 3 << 31 | 3);
+        assert_eq!(set.count(), 5);
+        assert!(set.test(63) && set.test(0) && !set.test(2));
+
+        set.clear(1);
+        set.clear(2);
+        assert!(!set.test(1));
+        assert_eq!(set.count(), 4);
+    }
+
+    #[test]
+    fn test_siphash13() {
+        // The outputs of the reference implementation of SipHash, with `cROUNDS = 1` and
+        // `dROUNDS = 3`, for the key 00..0f and the messages 00..i for i in 0..64
+        let expected: [u64; 64] = [
+            0xabac0158050fc4dc,
+            0xc9f49bf37d57ca93,
+            0x82cb9b024dc7d44d,
+            0x8bf80ab8e7ddf7fb,
+            0xcf75576088d38328,
+            0xdef9d52f49533b67,
+            0xc50d2b50c59f22a7,
+            0xd3927d989bb11140,
+            0x369095118d299a8e,
+            0x25a48eb36c063de4,
+            0x79de85ee92ff097f,
+            0x70c118c1f94dc352,
+            0x78a384b157b4d9a2,
+            0x306f760c1229ffa7,
+            0x605aa111c0f95d34,
+            0xd320d86d2a519956,
+            0xcc4fdd1a7d908b66,
+            0x9cf2689063dbd80c,
+            0x8ffc389cb473e63e,
+            0xf21f9de58d297d1c,
+            0xc0dc2f46a6cce040,
+            0xb992abfe2b45f844,
+            0x7ffe7b9ba320872e,
+            0x525a0e7fdae6c123,
+            0xf464aeb267349c8c,
+            0x45cd5928705b0979,
+            0x3a3e35e3ca9913a5,
+            0xa91dc74e4ade3b35,
+            0xfb0bed02ef6cd00d,
+            0x88d93cb44ab1e1f4,
+            0x540f11d643c5e663,
+            0x2370dd1f8c21d1bc,
+            0x81157b6c16a7b60d,
+            0x4d54b9e57a8ff9bf,
+            0x759f12781f2a753e,
+            0xcea1a3bebf186b91,
+            0x2cf508d3ada26206,
+            0xb6101c2da3c33057,
+            0xb3f47496ae3a36a1,
+            0x626b57547b108392,
+            0xc1d2363299e41531,
+            0x667cc1923f1ad944,
+            0x65704ffec8138825,
+            0x24f280d1c28949a6,
+            0xc2ca1cedfaf8876b,
+            0xc2164bfc9f042196,
+            0xa16e9c9368b1d623,
+            0x49fb169c8b5114fd,
+            0x9f3143f8df074c46,
+            0xc6fdaf2412cc86b3,
+            0x7eaf49d10a52098f,
+            0x1cf313559d292f9a,
+            0xc44a30dda2f41f12,
+            0x36fae98943a71ed0,
+            0x318fb34c73f0bce6,
+            0xa27abf3670a7e980,
+            0xb4bcc0db243c6d75,
+            0x23f8d852fdb71513,
+            0x8f035f4da67d8a08,
+            0xd89cd0e5b7e8f148,
+            0xf6f4e6bcf7a644ee,
+            0xaec59ad80f1837f2,
+            0xc3b2f6154b6694e0,
+            0x9d199062b7bbb3a8,
+        ];
+        let (k0, k1) = (0x0706050403020100, 0x0f0e0d0c0b0a0908);
+        let msg: Vec<u8> = (0..64).collect();
+        for (i, &hash) in expected.iter().enumerate() {
+            assert_eq!(siphash13(k0, k1, &msg[..i]), hash, "{i} bytes");
+        }
+    }
+
+    #[test]
+    fn test_pcg64si() {
+        let mut rng = Pcg64Si { state: 42 };
+        let outputs = [0; 3].map(|_| rng.next_u64());
+        assert_eq!(
+            outputs,
+            [
+                7101797662165212071,
+                11966180113123457027,
+                9748002374138552784
+            ]
+        );
+    }
+}